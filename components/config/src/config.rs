@@ -3,7 +3,7 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
 
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use syntect::parsing::{SyntaxSet, SyntaxSetBuilder};
 use toml;
@@ -16,6 +16,40 @@ use theme::Theme;
 // We want a default base url for tests
 static DEFAULT_BASE_URL: &'static str = "http://a-website.com";
 
+/// Which name to keep when several terms of a taxonomy have the same slug and are merged
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TermName {
+    /// The name used by the oldest page
+    FirstSeen,
+    /// The name used by the most pages
+    MostUsed,
+}
+
+/// How to sort the pages of each term of a taxonomy
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PagesSortBy {
+    /// Most recent to oldest
+    Date,
+    /// Lower weight comes first
+    Weight,
+    /// Alphabetical order of the titles
+    Title,
+}
+
+/// How to sort the terms of a taxonomy
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TermsSortBy {
+    /// Alphabetical order of the names
+    Name,
+    /// Terms with the most pages first
+    PageCount,
+    /// In the order of `terms_order`
+    Custom,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Taxonomy {
@@ -27,6 +61,25 @@ pub struct Taxonomy {
     pub paginate_path: Option<String>,
     /// Whether to generate a RSS feed only for each taxonomy term, defaults to false
     pub rss: bool,
+    /// Which name to keep when several terms have the same slug and are merged,
+    /// defaults to `first_seen`
+    pub term_name: TermName,
+    /// How to sort the pages of each term, defaults to `date`.
+    /// Pages that can't be sorted that way are put at the end
+    pub sort_pages_by: PagesSortBy,
+    /// Whether to reverse the order of the pages of each term
+    pub reverse_pages: bool,
+    /// How to sort the terms, defaults to `name`
+    pub sort_terms_by: TermsSortBy,
+    /// The names or slugs of the terms in order when sorting them by `custom`,
+    /// the terms not in that list come after, sorted by name
+    pub terms_order: Vec<String>,
+    /// Whether terms are paths like `programming/rust`, creating a `programming` term
+    /// with a `rust` child term, defaults to false
+    pub hierarchical: bool,
+    /// Whether the terms of a hierarchical taxonomy also get the pages of their descendants,
+    /// defaults to false
+    pub include_descendants: bool,
 }
 
 impl Taxonomy {
@@ -41,7 +94,147 @@ impl Taxonomy {
 
 impl Default for Taxonomy {
     fn default() -> Taxonomy {
-        Taxonomy { name: String::new(), paginate_by: None, paginate_path: None, rss: false }
+        Taxonomy {
+            name: String::new(),
+            paginate_by: None,
+            paginate_path: None,
+            rss: false,
+            term_name: TermName::FirstSeen,
+            sort_pages_by: PagesSortBy::Date,
+            reverse_pages: false,
+            sort_terms_by: TermsSortBy::Name,
+            terms_order: Vec::new(),
+            hierarchical: false,
+            include_descendants: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Language {
+    /// The language code, used as the suffix of content files and the prefix of their URL
+    pub code: String,
+    /// Whether to generate a RSS feed for that language, defaults to `false`
+    pub rss: bool,
+    /// Whether to build a search index for that language, defaults to `false`
+    pub search: bool,
+}
+
+/// What the entries of the Atom feed contain
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AtomContent {
+    /// The page content
+    Full,
+    /// The page summary, falling back to the content for pages without one
+    Summary,
+}
+
+/// The format of the processed markdown images, as for the `resize_image` function
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    /// WebP for WebP sources, PNG for sources with an alpha channel and JPEG otherwise
+    Auto,
+    Jpg,
+    Png,
+    Webp,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarkdownImages {
+    /// Whether to process the colocated images of the markdown content, defaults to `false`
+    pub process: bool,
+    /// Images wider than this will be resized to that width, `0` meaning no limit
+    pub max_width: u32,
+    /// The quality of the processed images when encoded as JPEG or WebP, in percent
+    pub quality: u8,
+    /// The format of the processed images, defaults to `auto`
+    pub format: ImageFormat,
+    /// Whether to add `loading="lazy"` to the processed images, defaults to `true`
+    pub lazy_loading: bool,
+}
+
+impl Default for MarkdownImages {
+    fn default() -> MarkdownImages {
+        MarkdownImages {
+            process: false,
+            max_width: 1200,
+            quality: 75,
+            format: ImageFormat::Auto,
+            lazy_loading: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkChecker {
+    /// How long to wait for a response before considering a link broken, in seconds
+    pub timeout: u64,
+    /// How many links can be checked at the same time
+    pub concurrency: usize,
+    /// The minimum delay between two requests to the same domain, in milliseconds
+    pub domain_delay: u64,
+    /// How many times to retry a link answering with a 429 or a 5xx status code
+    pub retries: u32,
+    /// Links starting with one of those prefixes are not checked at all
+    pub skip_prefixes: Vec<String>,
+    /// Links starting with one of those prefixes are checked but not their anchor, if any
+    pub skip_anchor_prefixes: Vec<String>,
+    /// Whether broken links are reported as warnings rather than failing the build
+    pub failures_as_warnings: bool,
+}
+
+impl LinkChecker {
+    /// Whether that link should not be checked at all
+    pub fn should_skip(&self, link: &str) -> bool {
+        self.skip_prefixes.iter().any(|prefix| link.starts_with(prefix))
+    }
+
+    /// Whether the anchor of that link should not be checked
+    pub fn should_skip_anchor(&self, link: &str) -> bool {
+        self.skip_anchor_prefixes.iter().any(|prefix| link.starts_with(prefix))
+    }
+}
+
+impl Default for LinkChecker {
+    fn default() -> LinkChecker {
+        LinkChecker {
+            timeout: 10,
+            concurrency: 16,
+            domain_delay: 100,
+            retries: 2,
+            skip_prefixes: Vec::new(),
+            skip_anchor_prefixes: Vec::new(),
+            failures_as_warnings: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RelatedPages {
+    /// The maximum number of related pages of each page, `0` disabling them
+    pub limit: usize,
+    /// How much a shared term of each taxonomy is worth, taxonomies not listed are not used
+    pub taxonomies: HashMap<String, f64>,
+    /// How much is added to the score of the newest page, older pages getting proportionally less
+    pub recency_weight: f64,
+}
+
+impl RelatedPages {
+    /// Whether related pages need to be computed at all
+    pub fn is_enabled(&self) -> bool {
+        self.limit > 0 && !self.taxonomies.is_empty()
+    }
+}
+
+impl Default for RelatedPages {
+    fn default() -> RelatedPages {
+        RelatedPages { limit: 5, taxonomies: HashMap::new(), recency_weight: 0.5 }
     }
 }
 
@@ -57,9 +250,13 @@ pub struct Config {
     pub title: Option<String>,
     /// Description of the site
     pub description: Option<String>,
+    /// Author of the site, used in the Atom feed. Defaults to None
+    pub author: Option<String>,
 
     /// The language used in the site. Defaults to "en"
     pub default_language: String,
+    /// The list of supported languages outside of the default one
+    pub languages: Vec<Language>,
     /// Languages list and translated strings
     pub translations: HashMap<String, Toml>,
 
@@ -71,15 +268,29 @@ pub struct Config {
 
     /// Whether to generate RSS. Defaults to false
     pub generate_rss: bool,
+    /// Whether to generate an Atom feed. Defaults to false
+    pub generate_atom: bool,
+    /// What the Atom entries contain, defaults to `full`
+    pub atom_content: AtomContent,
     /// The number of articles to include in the RSS feed. Defaults to including all items.
     pub rss_limit: Option<usize>,
 
     pub taxonomies: Vec<Taxonomy>,
+    /// How to find the related pages of each page from the taxonomy terms they share
+    pub related_pages: RelatedPages,
 
     /// Whether to compile the `sass` directory and output the css files into the static folder
     pub compile_sass: bool,
     /// Whether to build the search index for the content
     pub build_search_index: bool,
+    /// Whether several pages, sections, taxonomies or aliases rendered at the same path
+    /// only cause a warning instead of failing the build. Defaults to false
+    pub path_collisions_as_warnings: bool,
+    /// Whether to publish pages with a date in the future. Defaults to true
+    pub build_future: bool,
+    /// Whether to set the `last_modified` date of pages and sections from the git history
+    /// of their file, or from the filesystem outside of a git repository. Defaults to false
+    pub git_last_modified: bool,
     /// A list of file glob patterns to ignore when processing the content folder. Defaults to none.
     /// Had to remove the PartialEq derive because GlobSet does not implement it. No impact
     /// because it's unused anyway (who wants to sort Configs?).
@@ -89,6 +300,11 @@ pub struct Config {
 
     /// Whether to check all external links for validity
     pub check_external_links: bool,
+    /// How the external links are checked
+    pub link_checker: LinkChecker,
+
+    /// How to process the images found in the markdown content
+    pub markdown_images: MarkdownImages,
 
     /// A list of directories to search for additional `.sublime-syntax` files in.
     pub extra_syntaxes: Vec<String>,
@@ -120,6 +336,46 @@ impl Config {
             bail!("Highlight theme {} not available", config.highlight_theme)
         }
 
+        for (i, lang) in config.languages.iter().enumerate() {
+            if lang.code.is_empty() {
+                bail!("A language in `languages` is missing its `code`");
+            }
+            if lang.code == config.default_language {
+                bail!(
+                    "Language `{}` is the default language and cannot be in `languages`",
+                    lang.code
+                );
+            }
+            if config.languages[..i].iter().any(|l| l.code == lang.code) {
+                bail!("Language `{}` is defined more than once in `languages`", lang.code);
+            }
+        }
+
+        if config.link_checker.concurrency == 0 {
+            bail!("`concurrency` in `link_checker` must be greater than 0");
+        }
+
+        let images = &config.markdown_images;
+        if images.quality == 0 || images.quality > 100 {
+            bail!("`quality` in `markdown_images` must be in range 1-100");
+        }
+
+        for (name, weight) in &config.related_pages.taxonomies {
+            if !config.taxonomies.iter().any(|t| t.name == *name) {
+                bail!("Taxonomy `{}` in `related_pages` is not defined in `taxonomies`", name);
+            }
+            if !weight.is_finite() || *weight < 0.0 {
+                bail!(
+                    "The weight of taxonomy `{}` in `related_pages` must be a finite, non-negative number",
+                    name
+                );
+            }
+        }
+        let recency_weight = config.related_pages.recency_weight;
+        if !recency_weight.is_finite() || recency_weight < 0.0 {
+            bail!("`recency_weight` in `related_pages` must be a finite, non-negative number");
+        }
+
         config.build_timestamp = Some(Utc::now().timestamp());
 
         if !config.ignored_content.is_empty() {
@@ -157,6 +413,23 @@ impl Config {
         Config::parse(&content)
     }
 
+    /// Whether the site has content in more than one language
+    pub fn is_multilingual(&self) -> bool {
+        !self.languages.is_empty()
+    }
+
+    /// The codes of all the languages of the site, excluding the default one
+    pub fn languages_codes(&self) -> Vec<&str> {
+        self.languages.iter().map(|l| l.code.as_ref()).collect()
+    }
+
+    /// The time of the build in UTC, used for the pages in the future or expired so that
+    /// all of them are compared to the same time during a build
+    pub fn build_datetime(&self) -> NaiveDateTime {
+        let timestamp = self.build_timestamp.unwrap_or_else(|| Utc::now().timestamp());
+        NaiveDateTime::from_timestamp(timestamp, 0)
+    }
+
     /// Attempt to load any extra syntax found in the extra syntaxes of the config
     pub fn load_extra_syntaxes(&mut self, base_path: &Path) -> Result<()> {
         if self.extra_syntaxes.is_empty() {
@@ -174,7 +447,11 @@ impl Config {
 
     /// Makes a url, taking into account that the base url might have a trailing slash
     pub fn make_permalink(&self, path: &str) -> String {
-        let trailing_bit = if path.ends_with('/') || path.ends_with("rss.xml") || path.is_empty() {
+        let trailing_bit = if path.ends_with('/')
+            || path.ends_with("rss.xml")
+            || path.ends_with("atom.xml")
+            || path.is_empty()
+        {
             ""
         } else {
             "/"
@@ -227,16 +504,26 @@ impl Default for Config {
             base_url: DEFAULT_BASE_URL.to_string(),
             title: None,
             description: None,
+            author: None,
             theme: None,
             highlight_code: false,
             highlight_theme: "base16-ocean-dark".to_string(),
             default_language: "en".to_string(),
+            languages: Vec::new(),
             generate_rss: false,
+            generate_atom: false,
+            atom_content: AtomContent::Full,
             rss_limit: None,
             taxonomies: Vec::new(),
+            related_pages: RelatedPages::default(),
             compile_sass: false,
             check_external_links: false,
+            link_checker: LinkChecker::default(),
+            markdown_images: MarkdownImages::default(),
             build_search_index: false,
+            path_collisions_as_warnings: false,
+            build_future: true,
+            git_last_modified: false,
             ignored_content: Vec::new(),
             ignored_content_globset: None,
             translations: HashMap::new(),
@@ -250,7 +537,7 @@ impl Default for Config {
 
 #[cfg(test)]
 mod tests {
-    use super::{Config, Theme};
+    use super::{Config, ImageFormat, PagesSortBy, TermsSortBy, Theme};
 
     #[test]
     fn can_import_valid_config() {
@@ -343,6 +630,13 @@ hello = "world"
         assert_eq!(config.make_permalink("rss.xml"), "http://vincent.is/rss.xml");
     }
 
+    #[test]
+    fn doesnt_add_trailing_slash_to_atom() {
+        let mut config = Config::default();
+        config.base_url = "http://vincent.is/".to_string();
+        assert_eq!(config.make_permalink("atom.xml"), "http://vincent.is/atom.xml");
+    }
+
     #[test]
     fn can_merge_with_theme_data_and_preserve_config_value() {
         let config_str = r#"
@@ -387,6 +681,46 @@ title = "A title"
         assert_eq!(translations["en"]["title"].as_str().unwrap(), "A title");
     }
 
+    #[test]
+    fn can_parse_languages() {
+        let config = r#"
+base_url = "https://remplace-par-ton-url.fr"
+default_language = "fr"
+languages = [
+    {code = "en", rss = true},
+    {code = "it", search = true},
+]
+        "#;
+
+        let config = Config::parse(config).unwrap();
+        assert!(config.is_multilingual());
+        assert_eq!(config.languages_codes(), vec!["en", "it"]);
+        assert!(config.languages[0].rss);
+        assert!(!config.languages[0].search);
+        assert!(config.languages[1].search);
+    }
+
+    #[test]
+    fn errors_when_default_language_is_in_languages() {
+        let config = r#"
+base_url = "https://remplace-par-ton-url.fr"
+default_language = "fr"
+languages = [{code = "fr"}]
+        "#;
+
+        assert!(Config::parse(config).is_err());
+    }
+
+    #[test]
+    fn errors_on_duplicate_languages() {
+        let config = r#"
+base_url = "https://remplace-par-ton-url.fr"
+languages = [{code = "fr"}, {code = "fr", rss = true}]
+        "#;
+
+        assert!(Config::parse(config).is_err());
+    }
+
     #[test]
     fn missing_ignored_content_results_in_empty_vector_and_empty_globset() {
         let config_str = r#"
@@ -434,4 +768,206 @@ ignored_content = ["*.{graphml,iso}", "*.py?"]
         assert!(g.is_match("foo.py3"));
         assert!(!g.is_match("foo.py"));
     }
+
+    #[test]
+    fn can_parse_markdown_images() {
+        let config_str = r#"
+title = "My site"
+base_url = "example.com"
+
+[markdown_images]
+process = true
+max_width = 800
+format = "webp"
+        "#;
+
+        let config = Config::parse(config_str).unwrap();
+        assert!(config.markdown_images.process);
+        assert_eq!(config.markdown_images.max_width, 800);
+        assert_eq!(config.markdown_images.format, ImageFormat::Webp);
+        assert_eq!(config.markdown_images.quality, 75);
+        assert!(config.markdown_images.lazy_loading);
+    }
+
+    #[test]
+    fn errors_on_invalid_markdown_images_format() {
+        let config_str = r#"
+title = "My site"
+base_url = "example.com"
+
+[markdown_images]
+process = true
+format = "tiff"
+        "#;
+
+        let config = Config::parse(config_str);
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn errors_on_invalid_taxonomy_term_name() {
+        let config_str = r#"
+title = "My site"
+base_url = "example.com"
+taxonomies = [
+    {name = "tags", term_name = "last_seen"},
+]
+        "#;
+
+        let config = Config::parse(config_str);
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn can_parse_taxonomy_sorting() {
+        let config_str = r#"
+title = "My site"
+base_url = "example.com"
+taxonomies = [
+    {name = "series", sort_pages_by = "weight", reverse_pages = true},
+    {name = "tags", sort_terms_by = "custom", terms_order = ["rust", "python"]},
+]
+        "#;
+
+        let config = Config::parse(config_str).unwrap();
+        assert_eq!(config.taxonomies[0].sort_pages_by, PagesSortBy::Weight);
+        assert!(config.taxonomies[0].reverse_pages);
+        assert_eq!(config.taxonomies[0].sort_terms_by, TermsSortBy::Name);
+        assert_eq!(config.taxonomies[1].sort_pages_by, PagesSortBy::Date);
+        assert_eq!(config.taxonomies[1].sort_terms_by, TermsSortBy::Custom);
+        assert_eq!(config.taxonomies[1].terms_order, vec!["rust", "python"]);
+    }
+
+    #[test]
+    fn errors_on_invalid_taxonomy_sorting() {
+        let config_str = r#"
+title = "My site"
+base_url = "example.com"
+taxonomies = [
+    {name = "tags", sort_terms_by = "popularity"},
+]
+        "#;
+
+        let config = Config::parse(config_str);
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn can_parse_related_pages() {
+        let config_str = r#"
+title = "My site"
+base_url = "example.com"
+taxonomies = [
+    {name = "tags"},
+    {name = "categories"},
+]
+
+[related_pages]
+limit = 3
+taxonomies = {tags = 1.0, categories = 2.5}
+        "#;
+
+        let config = Config::parse(config_str).unwrap();
+        assert!(config.related_pages.is_enabled());
+        assert_eq!(config.related_pages.limit, 3);
+        assert_eq!(config.related_pages.taxonomies["categories"], 2.5);
+        assert_eq!(config.related_pages.recency_weight, 0.5);
+    }
+
+    #[test]
+    fn errors_on_related_pages_with_unknown_taxonomy() {
+        let config_str = r#"
+title = "My site"
+base_url = "example.com"
+taxonomies = [
+    {name = "tags"},
+]
+
+[related_pages]
+taxonomies = {authors = 1.0}
+        "#;
+
+        let config = Config::parse(config_str);
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn errors_on_related_pages_with_non_finite_weights() {
+        let config_str = r#"
+title = "My site"
+base_url = "example.com"
+taxonomies = [
+    {name = "tags"},
+]
+
+[related_pages]
+taxonomies = {tags = 1.0}
+recency_weight = nan
+        "#;
+        assert!(Config::parse(config_str).is_err());
+
+        let config_str = r#"
+title = "My site"
+base_url = "example.com"
+taxonomies = [
+    {name = "tags"},
+]
+
+[related_pages]
+taxonomies = {tags = inf}
+        "#;
+        assert!(Config::parse(config_str).is_err());
+    }
+
+    #[test]
+    fn errors_on_invalid_atom_content() {
+        let config_str = r#"
+title = "My site"
+base_url = "example.com"
+generate_atom = true
+atom_content = "excerpt"
+        "#;
+
+        let config = Config::parse(config_str);
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn can_parse_link_checker() {
+        let config_str = r#"
+title = "My site"
+base_url = "example.com"
+check_external_links = true
+
+[link_checker]
+timeout = 5
+concurrency = 4
+        "#;
+
+        let config = Config::parse(config_str).unwrap();
+        assert!(config.check_external_links);
+        assert_eq!(config.link_checker.timeout, 5);
+        assert_eq!(config.link_checker.concurrency, 4);
+        assert_eq!(config.link_checker.domain_delay, 100);
+        assert_eq!(config.link_checker.retries, 2);
+        assert!(!config.link_checker.failures_as_warnings);
+    }
+
+    #[test]
+    fn can_skip_links_by_prefix() {
+        let config_str = r#"
+title = "My site"
+base_url = "example.com"
+
+[link_checker]
+skip_prefixes = ["https://twitter.com/"]
+skip_anchor_prefixes = ["https://github.com/"]
+        "#;
+
+        let config = Config::parse(config_str).unwrap();
+        assert!(config.link_checker.should_skip("https://twitter.com/getzola"));
+        assert!(!config.link_checker.should_skip("https://github.com/getzola/zola"));
+        assert!(config.link_checker.should_skip_anchor("https://github.com/getzola/zola#readme"));
+        assert!(!config.link_checker.should_skip_anchor("https://docs.rs/zola#readme"));
+    }
 }
@@ -12,7 +12,10 @@ extern crate syntect;
 mod config;
 pub mod highlighting;
 mod theme;
-pub use config::{Config, Taxonomy};
+pub use config::{
+    AtomContent, Config, ImageFormat, Language, LinkChecker, MarkdownImages, PagesSortBy,
+    RelatedPages, Taxonomy, TermName, TermsSortBy,
+};
 
 use std::path::Path;
 
@@ -5,6 +5,7 @@ extern crate serde_derive;
 extern crate chrono;
 extern crate regex;
 extern crate serde;
+extern crate serde_yaml;
 extern crate tera;
 extern crate toml;
 
@@ -14,6 +15,7 @@ extern crate utils;
 
 use errors::{Result, ResultExt};
 use regex::Regex;
+use serde::de::DeserializeOwned;
 use std::path::Path;
 
 mod page;
@@ -23,8 +25,32 @@ pub use page::PageFrontMatter;
 pub use section::SectionFrontMatter;
 
 lazy_static! {
-    static ref PAGE_RE: Regex =
+    static ref TOML_RE: Regex =
         Regex::new(r"^[[:space:]]*\+\+\+\r?\n((?s).*?(?-s))\+\+\+\r?\n?((?s).*(?-s))$").unwrap();
+    // The closing `---` has to be on its own line since YAML values can contain `---`
+    static ref YAML_RE: Regex =
+        Regex::new(r"(?s)\A[[:space:]]*---\r?\n(.*?)(?m:^)---[ \t]*(?:\r?\n|\z)(.*)\z").unwrap();
+}
+
+/// The raw front matter of a file, before parsing: `+++` delimits TOML and `---` YAML
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RawFrontMatter<'a> {
+    Toml(&'a str),
+    Yaml(&'a str),
+}
+
+impl<'a> RawFrontMatter<'a> {
+    fn deserialize<T: DeserializeOwned>(&self) -> Result<T> {
+        let f: T = match *self {
+            RawFrontMatter::Toml(s) => toml::from_str(s)?,
+            RawFrontMatter::Yaml(s) => {
+                // An empty YAML document is not a mapping so we make it an empty one
+                let s = if s.trim().is_empty() { "{}" } else { s };
+                serde_yaml::from_str(s).map_err(|e| format!("YAML deserialize error: {}", e))?
+            }
+        };
+        Ok(f)
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
@@ -34,6 +60,12 @@ pub enum SortBy {
     Date,
     /// Lower weight comes first
     Weight,
+    /// Alphabetical order, ignoring case and accents and with numbers compared by value
+    Title,
+    /// Same as `Title` but on the slug
+    Slug,
+    /// By the value of the `extra` field named in `sort_by_extra`, lower values first
+    Extra,
     /// No sorting
     None,
 }
@@ -48,20 +80,30 @@ pub enum InsertAnchor {
 
 /// Split a file between the front matter and its content
 /// Will return an error if the front matter wasn't found
-fn split_content(file_path: &Path, content: &str) -> Result<(String, String)> {
-    if !PAGE_RE.is_match(content) {
+fn split_content<'c>(file_path: &Path, content: &'c str) -> Result<(RawFrontMatter<'c>, String)> {
+    let (re, is_toml) = if TOML_RE.is_match(content) {
+        (&TOML_RE as &Regex, true)
+    } else if YAML_RE.is_match(content) {
+        (&YAML_RE as &Regex, false)
+    } else {
         bail!(
-            "Couldn't find front matter in `{}`. Did you forget to add `+++`?",
+            "Couldn't find front matter in `{}`. Did you forget to add `+++` or `---`?",
             file_path.to_string_lossy()
         );
-    }
+    };
 
     // 2. extract the front matter and the content
-    let caps = PAGE_RE.captures(content).unwrap();
+    let caps = re.captures(content).unwrap();
     // caps[0] is the full match
     // caps[1] => front matter
     // caps[2] => content
-    Ok((caps[1].to_string(), caps[2].to_string()))
+    let front_matter = caps.get(1).unwrap().as_str();
+    let content = caps[2].to_string();
+    if is_toml {
+        Ok((RawFrontMatter::Toml(front_matter), content))
+    } else {
+        Ok((RawFrontMatter::Yaml(front_matter), content))
+    }
 }
 
 /// Split a file between the front matter and its content.
@@ -148,6 +190,73 @@ date = 2002-10-02T15:00:00Z
         assert_eq!(front_matter.title.unwrap(), "Title");
     }
 
+    #[test]
+    fn can_split_yaml_page_content_valid() {
+        let content = r#"
+---
+title: Title
+description: hey there
+date: 2002-10-12
+---
+Hello
+"#;
+        let (front_matter, content) = split_page_content(Path::new(""), content).unwrap();
+        assert_eq!(content, "Hello\n");
+        assert_eq!(front_matter.title.unwrap(), "Title");
+        assert_eq!(front_matter.date.unwrap(), "2002-10-12");
+    }
+
+    #[test]
+    fn can_split_yaml_section_content_valid() {
+        let content = r#"
+---
+paginate_by: 10
+---
+Hello
+"#;
+        let (front_matter, content) = split_section_content(Path::new(""), content).unwrap();
+        assert_eq!(content, "Hello\n");
+        assert!(front_matter.is_paginated());
+    }
+
+    #[test]
+    fn can_split_yaml_content_with_markdown_rule() {
+        let content = r#"
+---
+title: Title
+---
+Hello
+---
+World"#;
+        let (front_matter, content) = split_page_content(Path::new(""), content).unwrap();
+        assert_eq!(content, "Hello\n---\nWorld");
+        assert_eq!(front_matter.title.unwrap(), "Title");
+    }
+
+    #[test]
+    fn can_split_yaml_content_with_dashes_in_values() {
+        let content = r#"
+---
+title: "A --- B"
+description: |
+  Before
+  ---
+  After
+---
+Hello"#;
+        let (front_matter, content) = split_page_content(Path::new(""), content).unwrap();
+        assert_eq!(content, "Hello");
+        assert_eq!(front_matter.title.unwrap(), "A --- B");
+    }
+
+    #[test]
+    fn can_split_empty_yaml_front_matter() {
+        let content = "---\n---\nHello";
+        let (front_matter, content) = split_page_content(Path::new(""), content).unwrap();
+        assert_eq!(content, "Hello");
+        assert!(front_matter.title.is_none());
+    }
+
     #[test]
     fn errors_if_cannot_locate_frontmatter() {
         let content = r#"
@@ -158,4 +267,14 @@ date = 2002-10-12"#;
         let res = split_page_content(Path::new(""), content);
         assert!(res.is_err());
     }
+
+    #[test]
+    fn errors_if_cannot_locate_yaml_frontmatter() {
+        let content = r#"
+---
+title: Title
+description: hey there"#;
+        let res = split_page_content(Path::new(""), content);
+        assert!(res.is_err());
+    }
 }
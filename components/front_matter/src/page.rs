@@ -2,11 +2,12 @@ use std::collections::HashMap;
 
 use chrono::prelude::*;
 use tera::{Map, Value};
-use toml;
 
 use errors::Result;
 use utils::de::{fix_toml_dates, from_toml_datetime};
 
+use RawFrontMatter;
+
 /// The front matter of every page
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(default)]
@@ -21,9 +22,24 @@ pub struct PageFrontMatter {
     /// Chrono converted datetime
     #[serde(default, skip_deserializing)]
     pub datetime: Option<NaiveDateTime>,
+    /// The datetime converted to UTC, to compare it with the build time
+    #[serde(default, skip_deserializing)]
+    pub datetime_utc: Option<NaiveDateTime>,
     /// The converted date into a (year, month, day) tuple
     #[serde(default, skip_deserializing)]
     pub datetime_tuple: Option<(i32, u32, u32)>,
+    /// Date of the last significant update of the page, used in the sitemap and feeds
+    #[serde(default, deserialize_with = "from_toml_datetime")]
+    pub updated: Option<String>,
+    /// Chrono converted update datetime, in UTC
+    #[serde(default, skip_deserializing)]
+    pub updated_datetime_utc: Option<NaiveDateTime>,
+    /// Date after which the page is removed from listings and feeds
+    #[serde(default, deserialize_with = "from_toml_datetime")]
+    pub expires: Option<String>,
+    /// Chrono converted expiry datetime, in UTC
+    #[serde(default, skip_deserializing)]
+    pub expires_datetime: Option<NaiveDateTime>,
     /// Whether this page is a draft and should be ignored for pagination etc
     pub draft: bool,
     /// The page slug. Will be used instead of the filename if present
@@ -38,6 +54,11 @@ pub struct PageFrontMatter {
     pub order: Option<usize>,
     /// Integer to use to order content. Highest is at the bottom, lowest first
     pub weight: Option<usize>,
+    /// The name of the series this page is a part of, series can span several sections
+    pub series: Option<String>,
+    /// The position of the page in its series. Pages without it come after the numbered
+    /// ones, sorted by date
+    pub series_part: Option<usize>,
     /// All aliases for that page. Zola will create HTML templates that will
     /// redirect to this
     #[serde(skip_serializing)]
@@ -54,11 +75,8 @@ pub struct PageFrontMatter {
 }
 
 impl PageFrontMatter {
-    pub fn parse(toml: &str) -> Result<PageFrontMatter> {
-        let mut f: PageFrontMatter = match toml::from_str(toml) {
-            Ok(d) => d,
-            Err(e) => bail!(e),
-        };
+    pub fn parse(raw: &RawFrontMatter) -> Result<PageFrontMatter> {
+        let mut f: PageFrontMatter = raw.deserialize()?;
 
         if let Some(ref slug) = f.slug {
             if slug == "" {
@@ -78,6 +96,7 @@ impl PageFrontMatter {
         };
 
         f.date_to_datetime();
+        f.expires_datetime = f.expires.as_ref().and_then(|d| parse_datetime_utc(d));
 
         Ok(f)
     }
@@ -85,17 +104,9 @@ impl PageFrontMatter {
     /// Converts the TOML datetime to a Chrono naive datetime
     /// Also grabs the year/month/day tuple that will be used in serialization
     pub fn date_to_datetime(&mut self) {
-        self.datetime = if let Some(ref d) = self.date {
-            if d.contains('T') {
-                DateTime::parse_from_rfc3339(&d).ok().and_then(|s| Some(s.naive_local()))
-            } else {
-                NaiveDate::parse_from_str(&d, "%Y-%m-%d")
-                    .ok()
-                    .and_then(|s| Some(s.and_hms(0, 0, 0)))
-            }
-        } else {
-            None
-        };
+        self.datetime = self.date.as_ref().and_then(|d| parse_datetime(d));
+        self.datetime_utc = self.date.as_ref().and_then(|d| parse_datetime_utc(d));
+        self.updated_datetime_utc = self.updated.as_ref().and_then(|d| parse_datetime_utc(d));
 
         self.datetime_tuple = if let Some(ref dt) = self.datetime {
             Some((dt.year(), dt.month(), dt.day()))
@@ -113,6 +124,28 @@ impl PageFrontMatter {
     }
 }
 
+/// Parses the string of a TOML date or datetime into a Chrono naive datetime
+fn parse_datetime(d: &str) -> Option<NaiveDateTime> {
+    if d.contains('T') {
+        // TOML datetimes can omit the offset
+        DateTime::parse_from_rfc3339(d)
+            .ok()
+            .map(|s| s.naive_local())
+            .or_else(|| NaiveDateTime::parse_from_str(d, "%Y-%m-%dT%H:%M:%S%.f").ok())
+    } else {
+        NaiveDate::parse_from_str(d, "%Y-%m-%d").ok().map(|s| s.and_hms(0, 0, 0))
+    }
+}
+
+/// Same as `parse_datetime` but keeping the offset of the datetime by converting it to UTC.
+/// Dates and datetimes without offset are considered to be in UTC
+fn parse_datetime_utc(d: &str) -> Option<NaiveDateTime> {
+    match DateTime::parse_from_rfc3339(d) {
+        Ok(dt) => Some(dt.naive_utc()),
+        Err(_) => parse_datetime(d),
+    }
+}
+
 impl Default for PageFrontMatter {
     fn default() -> PageFrontMatter {
         PageFrontMatter {
@@ -120,13 +153,20 @@ impl Default for PageFrontMatter {
             description: None,
             date: None,
             datetime: None,
+            datetime_utc: None,
             datetime_tuple: None,
+            updated: None,
+            updated_datetime_utc: None,
+            expires: None,
+            expires_datetime: None,
             draft: false,
             slug: None,
             path: None,
             taxonomies: HashMap::new(),
             order: None,
             weight: None,
+            series: None,
+            series_part: None,
             aliases: Vec::new(),
             in_search_index: true,
             template: None,
@@ -138,12 +178,13 @@ impl Default for PageFrontMatter {
 #[cfg(test)]
 mod tests {
     use super::PageFrontMatter;
+    use super::RawFrontMatter;
     use tera::to_value;
 
     #[test]
     fn can_have_empty_front_matter() {
         let content = r#"  "#;
-        let res = PageFrontMatter::parse(content);
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content));
         println!("{:?}", res);
         assert!(res.is_ok());
     }
@@ -153,7 +194,7 @@ mod tests {
         let content = r#"
     title = "Hello"
     description = "hey there""#;
-        let res = PageFrontMatter::parse(content);
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content));
         assert!(res.is_ok());
         let res = res.unwrap();
         assert_eq!(res.title.unwrap(), "Hello".to_string());
@@ -163,7 +204,7 @@ mod tests {
     #[test]
     fn errors_with_invalid_front_matter() {
         let content = r#"title = 1\n"#;
-        let res = PageFrontMatter::parse(content);
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content));
         assert!(res.is_err());
     }
 
@@ -173,7 +214,7 @@ mod tests {
     title = "Hello"
     description = "hey there"
     slug = """#;
-        let res = PageFrontMatter::parse(content);
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content));
         assert!(res.is_err());
     }
 
@@ -183,7 +224,7 @@ mod tests {
     title = "Hello"
     description = "hey there"
     path = """#;
-        let res = PageFrontMatter::parse(content);
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content));
         assert!(res.is_err());
     }
 
@@ -194,7 +235,7 @@ mod tests {
     description = "hey there"
     date = 2016-10-10
     "#;
-        let res = PageFrontMatter::parse(content).unwrap();
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content)).unwrap();
         assert!(res.date.is_some());
     }
 
@@ -205,17 +246,66 @@ mod tests {
     description = "hey there"
     date = 2002-10-02T15:00:00Z
     "#;
-        let res = PageFrontMatter::parse(content).unwrap();
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content)).unwrap();
         assert!(res.date.is_some());
     }
 
+    #[test]
+    fn can_parse_updated_date() {
+        let content = r#"
+    title = "Hello"
+    date = 2002-10-02
+    updated = 2002-10-05T15:00:00Z
+    "#;
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content)).unwrap();
+        assert_eq!(res.updated, Some("2002-10-05T15:00:00Z".to_string()));
+    }
+
+    #[test]
+    fn can_parse_expiry_date() {
+        let content = r#"
+    title = "Hello"
+    date = 2002-10-02T15:00:00Z
+    expires = 2002-10-10
+    "#;
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content)).unwrap();
+        assert_eq!(res.expires, Some("2002-10-10".to_string()));
+        assert!(res.expires_datetime.unwrap() > res.datetime.unwrap());
+    }
+
+    #[test]
+    fn converts_dates_with_an_offset_to_utc() {
+        let content = r#"
+    title = "Hello"
+    date = 2002-10-02T15:00:00+02:00
+    expires = 2002-10-10T01:00:00-03:00
+    "#;
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content)).unwrap();
+        assert_eq!(res.datetime.unwrap().to_string(), "2002-10-02 15:00:00");
+        assert_eq!(res.datetime_utc.unwrap().to_string(), "2002-10-02 13:00:00");
+        assert_eq!(res.expires_datetime.unwrap().to_string(), "2002-10-10 04:00:00");
+    }
+
+    #[test]
+    fn can_parse_datetimes_without_offset() {
+        let content = r#"
+    title = "Hello"
+    date = 2002-10-02T15:00:00
+    updated = 2002-10-03T10:30:00
+    "#;
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content)).unwrap();
+        assert_eq!(res.datetime.unwrap().to_string(), "2002-10-02 15:00:00");
+        assert_eq!(res.datetime_utc.unwrap().to_string(), "2002-10-02 15:00:00");
+        assert_eq!(res.updated_datetime_utc.unwrap().to_string(), "2002-10-03 10:30:00");
+    }
+
     #[test]
     fn cannot_parse_random_date_format() {
         let content = r#"
     title = "Hello"
     description = "hey there"
     date = 2002/10/12"#;
-        let res = PageFrontMatter::parse(content);
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content));
         assert!(res.is_err());
     }
 
@@ -225,7 +315,7 @@ mod tests {
     title = "Hello"
     description = "hey there"
     date = 2002-14-01"#;
-        let res = PageFrontMatter::parse(content);
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content));
         assert!(res.is_err());
     }
 
@@ -235,7 +325,7 @@ mod tests {
     title = "Hello"
     description = "hey there"
     date = "2002-14-01""#;
-        let res = PageFrontMatter::parse(content);
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content));
         assert!(res.is_err());
     }
 
@@ -247,7 +337,7 @@ mod tests {
 
     [extra]
     some-date = 2002-14-01"#;
-        let res = PageFrontMatter::parse(content);
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content));
         println!("{:?}", res);
         assert!(res.is_ok());
         assert_eq!(res.unwrap().extra["some-date"], to_value("2002-14-01").unwrap());
@@ -261,7 +351,7 @@ mod tests {
 
     [extra.something]
     some-date = 2002-14-01"#;
-        let res = PageFrontMatter::parse(content);
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content));
         println!("{:?}", res);
         assert!(res.is_ok());
         assert_eq!(res.unwrap().extra["something"]["some-date"], to_value("2002-14-01").unwrap());
@@ -276,11 +366,65 @@ title = "Hello World"
 tags = ["Rust", "JavaScript"]
 categories = ["Dev"]
 "#;
-        let res = PageFrontMatter::parse(content);
+        let res = PageFrontMatter::parse(&RawFrontMatter::Toml(content));
         println!("{:?}", res);
         assert!(res.is_ok());
         let res2 = res.unwrap();
         assert_eq!(res2.taxonomies["categories"], vec!["Dev"]);
         assert_eq!(res2.taxonomies["tags"], vec!["Rust", "JavaScript"]);
     }
+
+    #[test]
+    fn can_parse_yaml_front_matter() {
+        let content = r#"
+title: Hello
+description: hey there
+draft: true
+weight: 2
+taxonomies:
+  tags:
+    - rust
+    - yaml
+extra:
+  author: Bob"#;
+        let res = PageFrontMatter::parse(&RawFrontMatter::Yaml(content)).unwrap();
+        assert_eq!(res.title.unwrap(), "Hello");
+        assert_eq!(res.description.unwrap(), "hey there");
+        assert!(res.draft);
+        assert_eq!(res.weight, Some(2));
+        assert_eq!(res.taxonomies["tags"], vec!["rust", "yaml"]);
+        assert_eq!(res.extra["author"], to_value("Bob").unwrap());
+    }
+
+    #[test]
+    fn can_parse_yaml_dates() {
+        let content = r#"
+title: Hello
+date: 2002-10-02T15:00:00Z"#;
+        let res = PageFrontMatter::parse(&RawFrontMatter::Yaml(content)).unwrap();
+        assert_eq!(res.date.unwrap(), "2002-10-02T15:00:00Z");
+        assert_eq!(res.datetime_tuple, Some((2002, 10, 2)));
+
+        let content = r#"
+title: Hello
+date: 2016-10-10"#;
+        let res = PageFrontMatter::parse(&RawFrontMatter::Yaml(content)).unwrap();
+        assert!(res.datetime.is_some());
+    }
+
+    #[test]
+    fn cannot_parse_invalid_yaml_date() {
+        let content = r#"
+title: Hello
+date: 2002-14-01"#;
+        let res = PageFrontMatter::parse(&RawFrontMatter::Yaml(content));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn errors_with_invalid_yaml_front_matter() {
+        let content = r#"title: [1"#;
+        let res = PageFrontMatter::parse(&RawFrontMatter::Yaml(content));
+        assert!(res.is_err());
+    }
 }
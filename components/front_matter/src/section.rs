@@ -1,14 +1,21 @@
 use std::collections::HashMap;
 
+use regex::Regex;
 use tera::Value;
-use toml;
 
 use errors::Result;
 
-use super::{InsertAnchor, SortBy};
+use super::{InsertAnchor, RawFrontMatter, SortBy};
 
 static DEFAULT_PAGINATE_PATH: &'static str = "page";
 
+/// The placeholders that can be used in `page_path`
+pub const PAGE_PATH_PLACEHOLDERS: [&str; 5] = ["year", "month", "day", "slug", "section"];
+
+lazy_static! {
+    static ref PAGE_PATH_PLACEHOLDER_RE: Regex = Regex::new(r"\{([^}]*)\}").unwrap();
+}
+
 /// The front matter of every section
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default)]
@@ -17,9 +24,15 @@ pub struct SectionFrontMatter {
     pub title: Option<String>,
     /// Description in <meta> that appears when linked, e.g. on twitter
     pub description: Option<String>,
-    /// Whether to sort by "date", "order", "weight" or "none". Defaults to `none`.
+    /// Whether to sort by "date", "weight", "title", "slug", "extra" or "none". Defaults to `none`.
     #[serde(skip_serializing)]
     pub sort_by: SortBy,
+    /// The name of the `extra` field of the pages to sort by if `sort_by` is `extra`
+    #[serde(skip_serializing)]
+    pub sort_by_extra: Option<String>,
+    /// Whether to reverse the order of the sorted pages, eg oldest to most recent. Defaults to `false`.
+    #[serde(skip_serializing)]
+    pub reverse_sort: bool,
     /// Used by the parent section to order its subsections.
     /// Higher values means it will be at the end. Defaults to `0`
     #[serde(skip_serializing)]
@@ -58,16 +71,41 @@ pub struct SectionFrontMatter {
     /// Optional template for all pages in this section (including the pages of children section)
     #[serde(skip_serializing)]
     pub page_template: Option<String>,
+    /// Optional pattern for the path of all pages in this section (including the pages of
+    /// transparent children sections), eg `/blog/{year}/{month}/{slug}/`.
+    /// Pages setting their own `path` are not affected
+    #[serde(skip_serializing)]
+    pub page_path: Option<String>,
+    /// Whether to generate a feed for the pages of that section, including the ones coming
+    /// from transparent subsections. Defaults to `false`.
+    #[serde(skip_serializing)]
+    pub generate_rss: bool,
     /// Any extra parameter present in the front matter
     pub extra: HashMap<String, Value>,
 }
 
 impl SectionFrontMatter {
-    pub fn parse(toml: &str) -> Result<SectionFrontMatter> {
-        let f: SectionFrontMatter = match toml::from_str(toml) {
-            Ok(d) => d,
-            Err(e) => bail!(e),
-        };
+    pub fn parse(raw: &RawFrontMatter) -> Result<SectionFrontMatter> {
+        let f: SectionFrontMatter = raw.deserialize()?;
+
+        if f.sort_by == SortBy::Extra && f.sort_by_extra.as_ref().map_or(true, |e| e.is_empty()) {
+            bail!("`sort_by_extra` needs to be set to the name of an `extra` field when sorting by `extra`")
+        }
+
+        if let Some(ref page_path) = f.page_path {
+            if page_path.trim().is_empty() {
+                bail!("`page_path` can't be empty if present")
+            }
+            for caps in PAGE_PATH_PLACEHOLDER_RE.captures_iter(page_path) {
+                if !PAGE_PATH_PLACEHOLDERS.contains(&&caps[1]) {
+                    bail!(
+                        "Unknown placeholder `{}` in `page_path`, the available ones are: {}",
+                        &caps[0],
+                        PAGE_PATH_PLACEHOLDERS.join(", ")
+                    )
+                }
+            }
+        }
 
         Ok(f)
     }
@@ -87,6 +125,8 @@ impl Default for SectionFrontMatter {
             title: None,
             description: None,
             sort_by: SortBy::None,
+            sort_by_extra: None,
+            reverse_sort: false,
             weight: 0,
             template: None,
             paginate_by: None,
@@ -97,7 +137,56 @@ impl Default for SectionFrontMatter {
             in_search_index: true,
             transparent: false,
             page_template: None,
+            page_path: None,
+            generate_rss: false,
             extra: HashMap::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::{RawFrontMatter, SortBy};
+    use super::SectionFrontMatter;
+
+    #[test]
+    fn can_parse_page_path() {
+        let content = r#"page_path = "/blog/{year}/{month}/{slug}/""#;
+        let res = SectionFrontMatter::parse(&RawFrontMatter::Toml(content)).unwrap();
+        assert_eq!(res.page_path, Some("/blog/{year}/{month}/{slug}/".to_string()));
+    }
+
+    #[test]
+    fn errors_on_unknown_page_path_placeholder() {
+        let content = r#"page_path = "/blog/{category}/{slug}/""#;
+        let res = SectionFrontMatter::parse(&RawFrontMatter::Toml(content));
+        assert!(res.is_err());
+        assert!(res.unwrap_err().to_string().contains("`{category}`"));
+    }
+
+    #[test]
+    fn errors_on_present_but_empty_page_path() {
+        let content = r#"page_path = "  ""#;
+        let res = SectionFrontMatter::parse(&RawFrontMatter::Toml(content));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn can_parse_sort_by_extra() {
+        let content = r#"
+sort_by = "extra"
+sort_by_extra = "order"
+reverse_sort = true"#;
+        let res = SectionFrontMatter::parse(&RawFrontMatter::Toml(content)).unwrap();
+        assert_eq!(res.sort_by, SortBy::Extra);
+        assert_eq!(res.sort_by_extra, Some("order".to_string()));
+        assert!(res.reverse_sort);
+    }
+
+    #[test]
+    fn errors_on_sort_by_extra_without_field() {
+        let content = r#"sort_by = "extra""#;
+        let res = SectionFrontMatter::parse(&RawFrontMatter::Toml(content));
+        assert!(res.is_err());
+    }
+}
@@ -1,21 +1,30 @@
 #[macro_use]
 extern crate lazy_static;
+#[macro_use]
+extern crate serde_derive;
+extern crate exif;
 extern crate image;
 extern crate rayon;
 extern crate regex;
+extern crate webp;
 
 extern crate errors;
 extern crate utils;
 
+#[cfg(test)]
+extern crate tempfile;
+
 use std::collections::hash_map::DefaultHasher;
 use std::collections::hash_map::Entry as HEntry;
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::hash::{Hash, Hasher};
+use std::io::{Cursor, Write};
 use std::path::{Path, PathBuf};
 
 use image::jpeg::JPEGEncoder;
-use image::{FilterType, GenericImageView};
+use image::png::PNGEncoder;
+use image::{ColorType, FilterType, GenericImageView, ImageFormat};
 use rayon::prelude::*;
 use regex::Regex;
 
@@ -26,7 +35,7 @@ static RESIZED_SUBDIR: &'static str = "processed_images";
 
 lazy_static! {
     pub static ref RESIZED_FILENAME: Regex =
-        Regex::new(r#"([0-9a-f]{16})([0-9a-f]{2})[.]jpg"#).unwrap();
+        Regex::new(r#"([0-9a-f]{16})([0-9a-f]{2})[.](jpg|png|webp)"#).unwrap();
 }
 
 /// Describes the precise kind of a resize operation
@@ -108,6 +117,35 @@ impl ResizeOp {
             Fill(_, h) => Some(h),
         }
     }
+
+    /// Returns the dimensions of the image this operation will produce from
+    /// a source image of `src_w` by `src_h` pixels
+    pub fn output_dimensions(self, src_w: u32, src_h: u32) -> (u32, u32) {
+        use ResizeOp::*;
+
+        match self {
+            Scale(w, h) => (w, h),
+            FitWidth(w) => fit_dimensions(src_w, src_h, w, ::std::u32::MAX),
+            FitHeight(h) => fit_dimensions(src_w, src_h, ::std::u32::MAX, h),
+            Fit(w, h) => fit_dimensions(src_w, src_h, w, h),
+            Fill(w, h) => (w, h),
+        }
+    }
+}
+
+/// Computes the largest dimensions fitting in `max_w` by `max_h` while preserving
+/// the aspect ratio, the same way `DynamicImage::resize` does
+fn fit_dimensions(src_w: u32, src_h: u32, max_w: u32, max_h: u32) -> (u32, u32) {
+    let ratio = u64::from(src_w) * u64::from(max_h);
+    let max_ratio = u64::from(max_w) * u64::from(src_h);
+
+    if max_ratio <= ratio {
+        let h = u64::from(src_h) * u64::from(max_w) / u64::from(src_w);
+        (max_w, h as u32)
+    } else {
+        let w = u64::from(src_w) * u64::from(max_h) / u64::from(src_h);
+        (w as u32, max_h)
+    }
 }
 
 impl From<ResizeOp> for u8 {
@@ -136,12 +174,84 @@ impl Hash for ResizeOp {
     }
 }
 
+/// Thumbnail image format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JPEG, the `u8` argument is the JPEG quality (in percent)
+    Jpeg(u8),
+    /// PNG, always lossless
+    Png,
+    /// WebP, the `u8` argument is the WebP quality (in percent)
+    WebP(u8),
+}
+
+impl Format {
+    /// `source_path` is the full path of the source image, only read with the `auto` format
+    pub fn from_args<P: AsRef<Path>>(source_path: P, format: &str, quality: u8) -> Result<Format> {
+        use Format::*;
+
+        if quality == 0 || quality > 100 {
+            return Err(format!("Quality must be within the range [1; 100], got {}", quality).into());
+        }
+        match format {
+            "auto" => Self::auto(source_path.as_ref(), quality),
+            "jpeg" | "jpg" => Ok(Jpeg(quality)),
+            "png" => Ok(Png),
+            "webp" => Ok(WebP(quality)),
+            _ => Err(format!("Invalid image format: {}", format).into()),
+        }
+    }
+
+    /// WebP sources stay WebP, sources with an alpha channel are encoded as PNG to keep it
+    /// and the other ones as JPEG
+    pub fn auto(source_path: &Path, quality: u8) -> Result<Format> {
+        use image::ColorType::*;
+
+        let bytes = fs::read(source_path)
+            .chain_err(|| format!("Failed to read image: {}", source_path.display()))?;
+        let format = guess_format(&bytes)
+            .chain_err(|| format!("Unsupported image file: {}", source_path.display()))?;
+        if format == ImageFormat::WEBP {
+            return Ok(Format::WebP(quality));
+        }
+
+        match decode_color_type(&bytes, format)? {
+            GrayA(_) | RGBA(_) | BGRA(_) => Ok(Format::Png),
+            _ => Ok(Format::Jpeg(quality)),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        // Please keep this in sync with RESIZED_FILENAME
+        match self {
+            Format::Jpeg(_) => "jpg",
+            Format::Png => "png",
+            Format::WebP(_) => "webp",
+        }
+    }
+}
+
+impl Hash for Format {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        use Format::*;
+
+        let q = match *self {
+            Png => 0,
+            Jpeg(q) => q,
+            WebP(q) => q,
+        };
+
+        hasher.write_u8(q);
+        hasher.write(self.extension().as_bytes());
+    }
+}
+
 /// Holds all data needed to perform a resize operation
 #[derive(Debug, PartialEq, Eq)]
 pub struct ImageOp {
     source: String,
     op: ResizeOp,
-    quality: u8,
+    format: Format,
     /// Hash of the above parameters
     hash: u64,
     /// If there is a hash collision with another ImageOp, this contains a sequential ID > 1
@@ -152,14 +262,14 @@ pub struct ImageOp {
 }
 
 impl ImageOp {
-    pub fn new(source: String, op: ResizeOp, quality: u8) -> ImageOp {
+    pub fn new(source: String, op: ResizeOp, format: Format) -> ImageOp {
         let mut hasher = DefaultHasher::new();
         hasher.write(source.as_ref());
         op.hash(&mut hasher);
-        hasher.write_u8(quality);
+        format.hash(&mut hasher);
         let hash = hasher.finish();
 
-        ImageOp { source, op, quality, hash, collision_id: 0 }
+        ImageOp { source, op, format, hash, collision_id: 0 }
     }
 
     pub fn from_args(
@@ -167,10 +277,10 @@ impl ImageOp {
         op: &str,
         width: Option<u32>,
         height: Option<u32>,
-        quality: u8,
+        format: Format,
     ) -> Result<ImageOp> {
         let op = ResizeOp::from_args(op, width, height)?;
-        Ok(Self::new(source, op, quality))
+        Ok(Self::new(source, op, format))
     }
 
     fn perform(&self, content_path: &Path, target_path: &Path) -> Result<()> {
@@ -223,9 +333,24 @@ impl ImageOp {
         };
 
         let mut f = File::create(target_path)?;
-        let mut enc = JPEGEncoder::new_with_quality(&mut f, self.quality);
         let (img_w, img_h) = img.dimensions();
-        enc.encode(&img.raw_pixels(), img_w, img_h, img.color())?;
+
+        match self.format {
+            Format::Png => {
+                let enc = PNGEncoder::new(&mut f);
+                enc.encode(&img.raw_pixels(), img_w, img_h, img.color())?;
+            }
+            Format::Jpeg(q) => {
+                let mut enc = JPEGEncoder::new_with_quality(&mut f, q);
+                enc.encode(&img.raw_pixels(), img_w, img_h, img.color())?;
+            }
+            Format::WebP(q) => {
+                let rgba = img.to_rgba();
+                let memory = webp::Encoder::from_rgba(&rgba, img_w, img_h).encode(f32::from(q));
+                f.write_all(&memory)?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -273,6 +398,16 @@ impl Processor {
         self.content_path.join(source).exists()
     }
 
+    /// Returns the full path of a source image, to read it without keeping the processor locked
+    pub fn source_path(&self, source: &str) -> PathBuf {
+        self.content_path.join(source)
+    }
+
+    /// Returns the width and height of a source image
+    pub fn source_dimensions(&self, source: &str) -> Result<(u32, u32)> {
+        read_image_dimensions(self.content_path.join(source))
+    }
+
     pub fn num_img_ops(&self) -> usize {
         self.img_ops.len() + self.img_ops_collisions.len()
     }
@@ -323,20 +458,21 @@ impl Processor {
         collision_id
     }
 
-    fn op_filename(hash: u64, collision_id: u32) -> String {
+    fn op_filename(hash: u64, collision_id: u32, format: Format) -> String {
         // Please keep this in sync with RESIZED_FILENAME
         assert!(collision_id < 256, "Unexpectedly large number of collisions: {}", collision_id);
-        format!("{:016x}{:02x}.jpg", hash, collision_id)
+        format!("{:016x}{:02x}.{}", hash, collision_id, format.extension())
     }
 
-    fn op_url(&self, hash: u64, collision_id: u32) -> String {
-        format!("{}/{}", &self.resized_url, Self::op_filename(hash, collision_id))
+    fn op_url(&self, hash: u64, collision_id: u32, format: Format) -> String {
+        format!("{}/{}", &self.resized_url, Self::op_filename(hash, collision_id, format))
     }
 
     pub fn insert(&mut self, img_op: ImageOp) -> String {
         let hash = img_op.hash;
+        let format = img_op.format;
         let collision_id = self.insert_with_collisions(img_op);
-        self.op_url(hash, collision_id)
+        self.op_url(hash, collision_id, format)
     }
 
     pub fn prune(&self) -> Result<()> {
@@ -373,7 +509,8 @@ impl Processor {
         self.img_ops
             .par_iter()
             .map(|(hash, op)| {
-                let target = self.resized_path.join(Self::op_filename(*hash, op.collision_id));
+                let target =
+                    self.resized_path.join(Self::op_filename(*hash, op.collision_id, op.format));
                 op.perform(&self.content_path, &target)
                     .chain_err(|| format!("Failed to process image: {}", op.source))
             })
@@ -381,6 +518,150 @@ impl Processor {
     }
 }
 
+/// Metadata of an image, as returned by `read_image_metadata`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImageMeta {
+    pub width: u32,
+    pub height: u32,
+    /// The file extension commonly used for that format, eg `jpg`
+    pub format: Option<&'static str>,
+    /// Only set if requested and if the image has any EXIF data
+    pub exif: Option<ExifMeta>,
+}
+
+/// The subset of the EXIF data of an image that is exposed to templates
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExifMeta {
+    /// When the picture was taken, formatted as `YYYY-MM-DDTHH:MM:SS`
+    pub date: Option<String>,
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub lens: Option<String>,
+    pub exposure_time: Option<String>,
+    pub f_number: Option<String>,
+    pub focal_length: Option<String>,
+    pub iso: Option<u32>,
+}
+
+impl ExifMeta {
+    fn from_exif(data: &exif::Exif) -> ExifMeta {
+        use exif::{In, Tag, Value};
+
+        let ascii = |tag: Tag| {
+            data.get_field(tag, In::PRIMARY).and_then(|f| match f.value {
+                Value::Ascii(ref v) => v
+                    .first()
+                    .map(|s| String::from_utf8_lossy(s).trim().to_string())
+                    .filter(|s| !s.is_empty()),
+                _ => None,
+            })
+        };
+        let display =
+            |tag: Tag| data.get_field(tag, In::PRIMARY).map(|f| f.display_value().to_string());
+
+        let date = data.get_field(Tag::DateTimeOriginal, In::PRIMARY).and_then(|f| match f.value {
+            Value::Ascii(ref v) if !v.is_empty() => exif::DateTime::from_ascii(&v[0]).ok(),
+            _ => None,
+        });
+
+        ExifMeta {
+            date: date.map(|d| {
+                format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                    d.year, d.month, d.day, d.hour, d.minute, d.second
+                )
+            }),
+            make: ascii(Tag::Make),
+            model: ascii(Tag::Model),
+            lens: ascii(Tag::LensModel),
+            exposure_time: display(Tag::ExposureTime),
+            f_number: display(Tag::FNumber),
+            focal_length: display(Tag::FocalLength),
+            iso: data
+                .get_field(Tag::PhotographicSensitivity, In::PRIMARY)
+                .and_then(|f| f.value.get_uint(0)),
+        }
+    }
+}
+
+/// Same as `image::guess_format` but also recognising WebP files, which start with a RIFF header
+fn guess_format(bytes: &[u8]) -> Result<ImageFormat> {
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Ok(ImageFormat::WEBP);
+    }
+    Ok(image::guess_format(bytes)?)
+}
+
+/// Reads the width and height of an image held in `bytes`, only decoding its header when possible
+fn decode_dimensions(bytes: &[u8], format: ImageFormat) -> Result<(u32, u32)> {
+    use image::ImageDecoder;
+
+    let reader = Cursor::new(bytes);
+    let dimensions = match format {
+        ImageFormat::PNG => image::png::PNGDecoder::new(reader).dimensions()?,
+        ImageFormat::JPEG => image::jpeg::JPEGDecoder::new(reader).dimensions()?,
+        ImageFormat::GIF => image::gif::Decoder::new(reader).dimensions()?,
+        ImageFormat::WEBP => image::webp::WebpDecoder::new(reader).dimensions()?,
+        ImageFormat::BMP => image::bmp::BMPDecoder::new(reader).dimensions()?,
+        _ => image::load_from_memory(bytes)?.dimensions(),
+    };
+
+    Ok(dimensions)
+}
+
+/// Reads the color type of an image held in `bytes`, only decoding its header when possible
+fn decode_color_type(bytes: &[u8], format: ImageFormat) -> Result<ColorType> {
+    use image::ImageDecoder;
+
+    let reader = Cursor::new(bytes);
+    let color_type = match format {
+        ImageFormat::PNG => image::png::PNGDecoder::new(reader).colortype()?,
+        ImageFormat::JPEG => image::jpeg::JPEGDecoder::new(reader).colortype()?,
+        ImageFormat::GIF => image::gif::Decoder::new(reader).colortype()?,
+        ImageFormat::BMP => image::bmp::BMPDecoder::new(reader).colortype()?,
+        _ => image::load_from_memory(bytes)?.color(),
+    };
+
+    Ok(color_type)
+}
+
+/// Reads the width and height of an image, only decoding its header when possible
+pub fn read_image_dimensions<P: AsRef<Path>>(path: P) -> Result<(u32, u32)> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).chain_err(|| format!("Failed to read image: {}", path.display()))?;
+    decode_dimensions(&bytes, guess_format(&bytes)?)
+}
+
+/// Reads the dimensions and format of an image and, if `with_exif` is true, its EXIF data
+pub fn read_image_metadata<P: AsRef<Path>>(path: P, with_exif: bool) -> Result<ImageMeta> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).chain_err(|| format!("Failed to read image: {}", path.display()))?;
+    let format = guess_format(&bytes)?;
+    let (width, height) = decode_dimensions(&bytes, format)?;
+
+    let format = match format {
+        ImageFormat::PNG => Some("png"),
+        ImageFormat::JPEG => Some("jpg"),
+        ImageFormat::GIF => Some("gif"),
+        ImageFormat::WEBP => Some("webp"),
+        ImageFormat::BMP => Some("bmp"),
+        ImageFormat::TIFF => Some("tiff"),
+        _ => None,
+    };
+
+    // Images without EXIF data, or with broken EXIF data, are not an error
+    let exif = if with_exif {
+        exif::Reader::new()
+            .read_from_container(&mut Cursor::new(&bytes))
+            .ok()
+            .map(|data| ExifMeta::from_exif(&data))
+    } else {
+        None
+    };
+
+    Ok(ImageMeta { width, height, format, exif })
+}
+
 /// Looks at file's extension and returns whether it's a supported image format
 pub fn file_is_img<P: AsRef<Path>>(p: P) -> bool {
     p.as_ref()
@@ -391,7 +672,97 @@ pub fn file_is_img<P: AsRef<Path>>(p: P) -> bool {
             "png" => true,
             "gif" => true,
             "bmp" => true,
+            "webp" => true,
             _ => false,
         })
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use tempfile::tempdir;
+    use webp;
+
+    use super::{read_image_dimensions, read_image_metadata, Format, ImageOp, Processor, ResizeOp};
+
+    #[test]
+    fn auto_format_keeps_sources_with_alpha_as_png() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let jpg = root.join("docs/content/documentation/content/image-processing/gutenberg.jpg");
+        assert_eq!(Format::from_args(&jpg, "auto", 75).unwrap(), Format::Jpeg(75));
+        // An RGBA PNG keeps its alpha channel
+        let png = root.join("test_site/content/posts/with-assets/zola.png");
+        assert_eq!(Format::from_args(&png, "auto", 75).unwrap(), Format::Png);
+        // but an RGB one doesn't have any to keep
+        let png = root.join("docs/content/themes/book/screenshot.png");
+        assert_eq!(Format::from_args(&png, "auto", 75).unwrap(), Format::Jpeg(75));
+        assert!(Format::from_args(root.join("README.md"), "auto", 75).is_err());
+        assert!(Format::from_args(root.join("a.jpg"), "auto", 75).is_err());
+    }
+
+    #[test]
+    fn auto_format_keeps_webp_sources_as_webp() {
+        let tmp_dir = tempdir().expect("create temp dir");
+        let path = tmp_dir.path().join("image.webp");
+        let pixels = [0u8, 0, 0, 0, 255, 255, 255, 128];
+        fs::write(&path, &*webp::Encoder::from_rgba(&pixels, 2, 1).encode(75.0)).unwrap();
+        assert_eq!(Format::from_args(&path, "auto", 50).unwrap(), Format::WebP(50));
+    }
+
+    #[test]
+    fn can_force_format() {
+        assert_eq!(Format::from_args("a.png", "jpg", 50).unwrap(), Format::Jpeg(50));
+        assert_eq!(Format::from_args("a.jpg", "png", 50).unwrap(), Format::Png);
+        assert_eq!(Format::from_args("a.jpg", "webp", 50).unwrap(), Format::WebP(50));
+        assert!(Format::from_args("a.jpg", "tiff", 50).is_err());
+    }
+
+    #[test]
+    fn errors_on_invalid_quality() {
+        assert!(Format::from_args("a.jpg", "jpg", 0).is_err());
+        assert!(Format::from_args("a.jpg", "auto", 101).is_err());
+    }
+
+    #[test]
+    fn format_is_part_of_hash_and_url() {
+        let op = ResizeOp::Fill(100, 100);
+        let jpg = ImageOp::new("a.png".to_string(), op, Format::Jpeg(75));
+        let png = ImageOp::new("a.png".to_string(), op, Format::Png);
+        assert_ne!(jpg.hash, png.hash);
+
+        let mut processor = Processor::new("content".into(), "static".as_ref(), "http://a.com");
+        assert!(processor.insert(jpg).ends_with(".jpg"));
+        assert!(processor.insert(png).ends_with(".png"));
+        let webp = ImageOp::new("a.png".to_string(), op, Format::WebP(75));
+        assert!(processor.insert(webp).ends_with(".webp"));
+    }
+
+    #[test]
+    fn can_compute_output_dimensions() {
+        assert_eq!(ResizeOp::Scale(100, 200).output_dimensions(300, 380), (100, 200));
+        assert_eq!(ResizeOp::FitWidth(150).output_dimensions(300, 380), (150, 190));
+        assert_eq!(ResizeOp::FitHeight(190).output_dimensions(300, 380), (150, 190));
+        assert_eq!(ResizeOp::Fit(150, 150).output_dimensions(300, 380), (118, 150));
+        assert_eq!(ResizeOp::Fill(150, 150).output_dimensions(300, 380), (150, 150));
+    }
+
+    #[test]
+    fn can_read_image_dimensions() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../docs/content/documentation/content/image-processing/gutenberg.jpg");
+        assert_eq!(read_image_dimensions(&path).unwrap(), (300, 380));
+    }
+
+    #[test]
+    fn can_read_image_metadata() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../test_site/content/posts/with-assets/zola.png");
+        let meta = read_image_metadata(&path, true).unwrap();
+        assert_eq!(meta.format, Some("png"));
+        assert_eq!((meta.width, meta.height), read_image_dimensions(&path).unwrap());
+        assert_eq!(meta.exif, None);
+    }
+}
@@ -1,5 +1,8 @@
 use std::path::{Path, PathBuf};
 
+use config::Config;
+use errors::Result;
+
 /// Takes a full path to a file and returns only the components after the first `content` directory
 /// Will not return the filename as last component
 pub fn find_content_components<P: AsRef<Path>>(path: P) -> Vec<String> {
@@ -28,8 +31,14 @@ pub fn find_content_components<P: AsRef<Path>>(path: P) -> Vec<String> {
 pub struct FileInfo {
     /// The full path to the .md file
     pub path: PathBuf,
-    /// The name of the .md file without the extension, always `_index` for sections
+    /// The on-disk filename, will differ from the `name` when there is a language code in it
+    pub filename: String,
+    /// The name of the .md file without the extension and the language code,
+    /// always `_index` for sections
     pub name: String,
+    /// The path of the file without its language code and extension, shared by all the
+    /// translations of the same content
+    pub canonical: PathBuf,
     /// The .md path, starting from the content directory, with `/` slashes
     pub relative: String,
     /// Path of the directory containing the .md file
@@ -53,20 +62,24 @@ impl FileInfo {
         } else {
             format!("{}.md", name)
         };
+        let canonical = parent.join(&name);
 
         // If we have a folder with an asset, don't consider it as a component
-        if !components.is_empty() && name == "index" {
+        // Only looking before the first `.` as it might be a translation, eg `index.fr`
+        if !components.is_empty() && name.split('.').next() == Some("index") {
             components.pop();
             // also set parent_path to grandparent instead
             parent = parent.parent().unwrap().to_path_buf();
         }
 
         FileInfo {
+            filename: file_path.file_name().unwrap().to_string_lossy().to_string(),
             path: file_path,
             // We don't care about grand parent for pages
             grand_parent: None,
             parent,
             name,
+            canonical,
             components,
             relative,
         }
@@ -74,17 +87,20 @@ impl FileInfo {
 
     pub fn new_section(path: &Path) -> FileInfo {
         let parent = path.parent().unwrap().to_path_buf();
+        let filename = path.file_name().unwrap().to_string_lossy().to_string();
         let components = find_content_components(path);
         let relative = if components.is_empty() {
             // the index one
-            "_index.md".to_string()
+            filename.clone()
         } else {
-            format!("{}/_index.md", components.join("/"))
+            format!("{}/{}", components.join("/"), filename)
         };
         let grand_parent = parent.parent().map(|p| p.to_path_buf());
 
         FileInfo {
             path: path.to_path_buf(),
+            canonical: parent.join("_index"),
+            filename,
             parent,
             grand_parent,
             name: "_index".to_string(),
@@ -92,6 +108,48 @@ impl FileInfo {
             relative,
         }
     }
+
+    /// Look for a language code in the filename, eg `about.fr.md` or `_index.fr.md`.
+    /// If one is found, it is removed from the `name` of the file and the code is returned,
+    /// otherwise it returns the default language of the site.
+    pub fn find_language(&mut self, config: &Config) -> Result<String> {
+        // No languages? Nothing to do
+        if !config.is_multilingual() {
+            return Ok(config.default_language.clone());
+        }
+
+        let stem = self.filename.trim_right_matches(".md");
+        if !stem.contains('.') {
+            return Ok(config.default_language.clone());
+        }
+
+        // We assume no one is using `.` in their filenames when using multiple languages
+        let mut parts = stem.splitn(2, '.');
+        let name = parts.next().unwrap().to_string();
+        let lang = parts.next().unwrap().to_string();
+
+        // The language code is not present in the config: typo or the user forgot to add it
+        if !config.languages_codes().contains(&lang.as_ref()) {
+            bail!(
+                "File {:?} has a language code of `{}` which isn't present in the config.toml `languages`",
+                self.path,
+                lang
+            );
+        }
+
+        self.canonical = self.path.parent().unwrap().join(&name);
+        self.name = name;
+
+        Ok(lang)
+    }
+
+    /// The directory containing the file, starting from the content directory, with `/` slashes
+    pub fn relative_dir(&self) -> String {
+        match self.relative.rfind('/') {
+            Some(i) => self.relative[..i].to_string(),
+            None => String::new(),
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -99,6 +157,8 @@ impl Default for FileInfo {
     fn default() -> FileInfo {
         FileInfo {
             path: PathBuf::new(),
+            filename: String::new(),
+            canonical: PathBuf::new(),
             parent: PathBuf::new(),
             grand_parent: None,
             name: String::new(),
@@ -110,7 +170,11 @@ impl Default for FileInfo {
 
 #[cfg(test)]
 mod tests {
-    use super::find_content_components;
+    use std::path::Path;
+
+    use config::{Config, Language};
+
+    use super::{find_content_components, FileInfo};
 
     #[test]
     fn can_find_content_components() {
@@ -118,4 +182,79 @@ mod tests {
             find_content_components("/home/vincent/code/site/content/posts/tutorials/python.md");
         assert_eq!(res, ["posts".to_string(), "tutorials".to_string()]);
     }
+
+    #[test]
+    fn can_get_relative_dir() {
+        let file = FileInfo::new_page(Path::new(
+            "/home/vincent/code/site/content/posts/tutorials/python/index.md",
+        ));
+        assert_eq!(file.relative_dir(), "posts/tutorials/python");
+        let file = FileInfo::new_section(Path::new("/home/vincent/code/site/content/_index.md"));
+        assert_eq!(file.relative_dir(), "");
+    }
+
+    #[test]
+    fn can_find_language_in_page() {
+        let mut config = Config::default();
+        config.languages.push(Language { code: String::from("fr"), ..Language::default() });
+        let mut file = FileInfo::new_page(Path::new(
+            "/home/vincent/code/site/content/posts/tutorials/python.fr.md",
+        ));
+        let res = file.find_language(&config);
+        assert_eq!(res.unwrap(), "fr");
+        assert_eq!(file.name, "python");
+        assert_eq!(
+            file.canonical,
+            Path::new("/home/vincent/code/site/content/posts/tutorials/python")
+        );
+        assert_eq!(file.relative, "posts/tutorials/python.fr.md");
+    }
+
+    #[test]
+    fn can_find_language_in_page_with_assets() {
+        let mut config = Config::default();
+        config.languages.push(Language { code: String::from("fr"), ..Language::default() });
+        let mut file = FileInfo::new_page(Path::new(
+            "/home/vincent/code/site/content/posts/tutorials/python/index.fr.md",
+        ));
+        assert_eq!(file.components, ["posts".to_string(), "tutorials".to_string()]);
+        let res = file.find_language(&config);
+        assert_eq!(res.unwrap(), "fr");
+        assert_eq!(file.name, "index");
+    }
+
+    #[test]
+    fn can_find_language_in_section() {
+        let mut config = Config::default();
+        config.languages.push(Language { code: String::from("fr"), ..Language::default() });
+        let mut file =
+            FileInfo::new_section(Path::new("/home/vincent/code/site/content/posts/_index.fr.md"));
+        let res = file.find_language(&config);
+        assert_eq!(res.unwrap(), "fr");
+        assert_eq!(file.name, "_index");
+        assert_eq!(file.relative, "posts/_index.fr.md");
+        assert_eq!(file.canonical, Path::new("/home/vincent/code/site/content/posts/_index"));
+    }
+
+    #[test]
+    fn uses_default_language_without_language_code() {
+        let mut config = Config::default();
+        config.languages.push(Language { code: String::from("fr"), ..Language::default() });
+        let mut file = FileInfo::new_page(Path::new(
+            "/home/vincent/code/site/content/posts/tutorials/python.md",
+        ));
+        let res = file.find_language(&config);
+        assert_eq!(res.unwrap(), config.default_language);
+        assert_eq!(file.name, "python");
+    }
+
+    #[test]
+    fn errors_on_unknown_language_code() {
+        let mut config = Config::default();
+        config.languages.push(Language { code: String::from("it"), ..Language::default() });
+        let mut file = FileInfo::new_page(Path::new(
+            "/home/vincent/code/site/content/posts/tutorials/python.fr.md",
+        ));
+        assert!(file.find_language(&config).is_err());
+    }
 }
@@ -1,7 +1,9 @@
 /// A page, can be a blog post or a basic page
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
+use chrono::NaiveDateTime;
 use regex::Regex;
 use slotmap::Key;
 use slug::slugify;
@@ -10,7 +12,8 @@ use tera::{Context as TeraContext, Tera};
 use config::Config;
 use errors::{Result, ResultExt};
 use front_matter::{split_page_content, InsertAnchor, PageFrontMatter};
-use library::Library;
+use imageproc::Processor;
+use library::{Backlink, Library};
 use rendering::{render_content, Header, RenderContext};
 use utils::fs::{find_related_assets, read_file};
 use utils::site::get_reading_analytics;
@@ -61,13 +64,34 @@ pub struct Page {
     pub lighter: Option<Key>,
     /// The heavier page, for pages sorted by weight
     pub heavier: Option<Key>,
+    /// The previous page, for pages sorted by title, slug or extra field
+    pub previous: Option<Key>,
+    /// The next page, for pages sorted by title, slug or extra field
+    pub next: Option<Key>,
     /// Toc made from the headers of the markdown file
     pub toc: Vec<Header>,
+    /// The external links of the markdown content, to be checked once everything is rendered
+    pub external_links: Vec<String>,
+    /// The content files this page links to, relative to the `content` directory
+    pub internal_links: Vec<String>,
+    /// The pages and sections linking to this page, filled once all the content is rendered
+    pub backlinks: Vec<Backlink>,
     /// How many words in the raw content
     pub word_count: Option<usize>,
     /// How long would it take to read the raw content.
     /// See `get_reading_analytics` on how it is calculated
     pub reading_time: Option<usize>,
+    /// The language of that page. Equal to the default lang if the user doesn't setup `languages` in config.
+    /// Corresponds to the lang in the {slug}.{lang}.md file scheme
+    pub lang: String,
+    /// Contains all the translated version of that page
+    pub translations: Vec<Key>,
+    /// All the pages of the series of that page in order, itself included
+    pub series: Vec<Key>,
+    /// The most related pages first, from the taxonomy terms they share with that page
+    pub related: Vec<Key>,
+    /// When the file of that page was last modified, only set if `git_last_modified` is enabled
+    pub last_modified: Option<String>,
 }
 
 impl Page {
@@ -91,9 +115,19 @@ impl Page {
             later: None,
             lighter: None,
             heavier: None,
+            previous: None,
+            next: None,
             toc: vec![],
+            external_links: vec![],
+            internal_links: vec![],
+            backlinks: vec![],
             word_count: None,
             reading_time: None,
+            lang: String::new(),
+            translations: Vec::new(),
+            series: Vec::new(),
+            related: Vec::new(),
+            last_modified: None,
         }
     }
 
@@ -101,12 +135,31 @@ impl Page {
         self.meta.draft
     }
 
+    /// Whether the date of the page is after `now`, the build time in UTC
+    /// from `Config::build_datetime`
+    pub fn is_in_future(&self, now: NaiveDateTime) -> bool {
+        match self.meta.datetime_utc {
+            Some(d) => d > now,
+            None => false,
+        }
+    }
+
+    /// Whether the `expires` date of the page is before `now`, the build time in UTC
+    /// from `Config::build_datetime`, in which case it is left out of listings and feeds
+    pub fn is_expired(&self, now: NaiveDateTime) -> bool {
+        match self.meta.expires_datetime {
+            Some(d) => d <= now,
+            None => false,
+        }
+    }
+
     /// Parse a page given the content of the .md file
     /// Files without front matter or with invalid front matter are considered
     /// erroneous
     pub fn parse(file_path: &Path, content: &str, config: &Config) -> Result<Page> {
         let (meta, content) = split_page_content(file_path, content)?;
         let mut page = Page::new(file_path, meta);
+        page.lang = page.file.find_language(config)?;
 
         page.raw_content = content;
         let (word_count, reading_time) = get_reading_analytics(&page.raw_content);
@@ -144,25 +197,68 @@ impl Page {
         if let Some(ref p) = page.meta.path {
             page.path = p.trim().trim_left_matches('/').to_string();
         } else {
-            page.path = if page.file.components.is_empty() {
+            let path = if page.file.components.is_empty() {
                 page.slug.clone()
             } else {
                 format!("{}/{}", page.file.components.join("/"), page.slug)
             };
+
+            page.path = if page.lang != config.default_language {
+                format!("{}/{}", page.lang, path)
+            } else {
+                path
+            };
         }
-        if !page.path.ends_with('/') {
-            page.path = format!("{}/", page.path);
-        }
+        let path = page.path.clone();
+        page.set_path(path, config);
 
-        page.components = page
+        Ok(page)
+    }
+
+    /// Sets the path of the page along with its components and permalink
+    fn set_path(&mut self, path: String, config: &Config) {
+        self.path = if path.ends_with('/') { path } else { format!("{}/", path) };
+        self.components = self
             .path
             .split('/')
             .map(|p| p.to_string())
             .filter(|p| !p.is_empty())
             .collect::<Vec<_>>();
-        page.permalink = config.make_permalink(&page.path);
+        self.permalink = config.make_permalink(&self.path);
+    }
 
-        Ok(page)
+    /// Replaces the path of the page by the one made from the `page_path` pattern of its section,
+    /// `section` being the path of the section defining that pattern.
+    /// The section of a page is not known when parsing it so this is done when adding it to the site.
+    /// Pages with a `path` in their front-matter are left untouched
+    pub fn apply_page_path(&mut self, pattern: &str, section: &str, config: &Config) -> Result<()> {
+        if self.meta.path.is_some() {
+            return Ok(());
+        }
+
+        let mut path = pattern.replace("{slug}", &self.slug).replace("{section}", section);
+        if path.contains("{year}") || path.contains("{month}") || path.contains("{day}") {
+            let (year, month, day) = match self.meta.datetime_tuple {
+                Some(d) => d,
+                None => bail!(
+                    "Page `{}` has no date but the `page_path` of its section needs one",
+                    self.file.path.display()
+                ),
+            };
+            path = path
+                .replace("{year}", &year.to_string())
+                .replace("{month}", &format!("{:02}", month))
+                .replace("{day}", &format!("{:02}", day));
+        }
+
+        // Placeholders can be empty, eg `{section}` for the index section
+        let mut path =
+            path.split('/').filter(|p| !p.trim().is_empty()).collect::<Vec<_>>().join("/");
+        if self.lang != config.default_language {
+            path = format!("{}/{}", self.lang, path);
+        }
+        self.set_path(path, config);
+        Ok(())
     }
 
     /// Read and parse a .md file into a Page struct
@@ -210,9 +306,12 @@ impl Page {
         tera: &Tera,
         config: &Config,
         anchor_insert: InsertAnchor,
+        imageproc: Option<&Mutex<Processor>>,
     ) -> Result<()> {
         let mut context =
             RenderContext::new(tera, config, &self.permalink, permalinks, anchor_insert);
+        context.imageproc = imageproc;
+        context.current_page_dir = self.file.relative_dir();
 
         context.tera_context.insert("page", &SerializingPage::from_page_basic(self, None));
 
@@ -222,6 +321,8 @@ impl Page {
         self.summary = res.summary_len.map(|l| res.body[0..l].to_owned());
         self.content = res.body;
         self.toc = res.toc;
+        self.external_links = res.external_links;
+        self.internal_links = res.internal_links;
 
         Ok(())
     }
@@ -238,6 +339,7 @@ impl Page {
         context.insert("current_url", &self.permalink);
         context.insert("current_path", &self.path);
         context.insert("page", &self.to_serialized(library));
+        context.insert("lang", &self.lang);
 
         render_template(&tpl_name, tera, &context, &config.theme)
             .chain_err(|| format!("Failed to render page '{}'", self.file.path.display()))
@@ -281,9 +383,19 @@ impl Default for Page {
             later: None,
             lighter: None,
             heavier: None,
+            previous: None,
+            next: None,
             toc: vec![],
+            external_links: vec![],
+            internal_links: vec![],
+            backlinks: vec![],
             word_count: None,
             reading_time: None,
+            lang: String::new(),
+            translations: Vec::new(),
+            series: Vec::new(),
+            related: Vec::new(),
+            last_modified: None,
         }
     }
 }
@@ -300,7 +412,7 @@ mod tests {
     use tera::Tera;
 
     use super::Page;
-    use config::Config;
+    use config::{Config, Language};
     use front_matter::InsertAnchor;
 
     #[test]
@@ -320,6 +432,7 @@ Hello world"#;
             &Tera::default(),
             &Config::default(),
             InsertAnchor::None,
+            None,
         )
         .unwrap();
 
@@ -428,8 +541,14 @@ Hello world
         let res = Page::parse(Path::new("hello.md"), &content, &config);
         assert!(res.is_ok());
         let mut page = res.unwrap();
-        page.render_markdown(&HashMap::default(), &Tera::default(), &config, InsertAnchor::None)
-            .unwrap();
+        page.render_markdown(
+            &HashMap::default(),
+            &Tera::default(),
+            &config,
+            InsertAnchor::None,
+            None,
+        )
+        .unwrap();
         assert_eq!(page.summary, Some("<p>Hello world</p>\n".to_string()));
     }
 
@@ -506,6 +625,69 @@ Hello world
         assert_eq!(page.assets[0].file_name().unwrap().to_str(), Some("graph.jpg"));
     }
 
+    #[test]
+    fn can_apply_page_path_of_section() {
+        let config = Config::default();
+        let content = r#"
++++
+date = 2017-04-09
++++
+Hello world"#
+            .to_string();
+        let mut page =
+            Page::parse(Path::new("content/posts/2017/my-post.md"), &content, &config).unwrap();
+        page.apply_page_path("/{section}/{year}/{month}/{day}/{slug}/", "posts", &config).unwrap();
+        assert_eq!(page.path, "posts/2017/04/09/my-post/");
+        assert_eq!(page.components, vec!["posts", "2017", "04", "09", "my-post"]);
+        assert_eq!(page.permalink, "http://a-website.com/posts/2017/04/09/my-post/");
+
+        // The index section has an empty path
+        page.apply_page_path("{section}/{year}/{slug}", "", &config).unwrap();
+        assert_eq!(page.path, "2017/my-post/");
+    }
+
+    #[test]
+    fn page_path_of_section_keeps_language_and_page_path() {
+        let mut config = Config::default();
+        config.languages.push(Language { code: String::from("fr"), ..Language::default() });
+        let content = r#"
++++
+date = 2017-04-09
++++
+Bonjour le monde"#
+            .to_string();
+        let mut page =
+            Page::parse(Path::new("content/posts/my-post.fr.md"), &content, &config).unwrap();
+        page.apply_page_path("/{year}/{slug}/", "posts", &config).unwrap();
+        assert_eq!(page.path, "fr/2017/my-post/");
+
+        let content = r#"
++++
+date = 2017-04-09
+path = "hello"
++++
+Hello world"#
+            .to_string();
+        let mut page =
+            Page::parse(Path::new("content/posts/my-post.md"), &content, &config).unwrap();
+        page.apply_page_path("/{year}/{slug}/", "posts", &config).unwrap();
+        assert_eq!(page.path, "hello/");
+    }
+
+    #[test]
+    fn errors_on_page_path_needing_a_date() {
+        let config = Config::default();
+        let content = r#"
++++
++++
+Hello world"#
+            .to_string();
+        let mut page =
+            Page::parse(Path::new("content/posts/my-post.md"), &content, &config).unwrap();
+        assert!(page.apply_page_path("/{section}/{slug}/", "posts", &config).is_ok());
+        assert!(page.apply_page_path("/{year}/{slug}/", "posts", &config).is_err());
+    }
+
     #[test]
     fn can_get_date_from_filename() {
         let config = Config::default();
@@ -540,4 +722,57 @@ Hello world
         assert_eq!(page.meta.date, Some("2018-09-09".to_string()));
         assert_eq!(page.slug, "hello");
     }
+
+    #[test]
+    fn can_specify_language_in_filename() {
+        let mut config = Config::default();
+        config.languages.push(Language { code: String::from("fr"), ..Language::default() });
+        let content = r#"
++++
++++
+Bonjour le monde"#
+            .to_string();
+        let res = Page::parse(Path::new("content/posts/hello.fr.md"), &content, &config);
+        assert!(res.is_ok());
+        let page = res.unwrap();
+        assert_eq!(page.lang, "fr".to_string());
+        assert_eq!(page.slug, "hello");
+        assert_eq!(page.path, "fr/posts/hello/");
+        assert_eq!(page.permalink, config.make_permalink("fr/posts/hello"));
+    }
+
+    #[test]
+    fn can_specify_language_in_filename_with_date() {
+        let mut config = Config::default();
+        config.languages.push(Language { code: String::from("fr"), ..Language::default() });
+        let content = r#"
++++
++++
+Bonjour le monde"#
+            .to_string();
+        let res = Page::parse(Path::new("2018-10-08_hello.fr.md"), &content, &config);
+        assert!(res.is_ok());
+        let page = res.unwrap();
+        assert_eq!(page.meta.date, Some("2018-10-08".to_string()));
+        assert_eq!(page.lang, "fr".to_string());
+        assert_eq!(page.slug, "hello");
+        assert_eq!(page.permalink, config.make_permalink("fr/hello"));
+    }
+
+    #[test]
+    fn i18n_frontmatter_path_overrides_default_permalink() {
+        let mut config = Config::default();
+        config.languages.push(Language { code: String::from("fr"), ..Language::default() });
+        let content = r#"
++++
+path = "bonjour"
++++
+Bonjour le monde"#
+            .to_string();
+        let res = Page::parse(Path::new("hello.fr.md"), &content, &config);
+        assert!(res.is_ok());
+        let page = res.unwrap();
+        assert_eq!(page.lang, "fr".to_string());
+        assert_eq!(page.permalink, config.make_permalink("bonjour"));
+    }
 }
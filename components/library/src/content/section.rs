@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use slotmap::Key;
 use tera::{Context as TeraContext, Tera};
@@ -7,6 +8,7 @@ use tera::{Context as TeraContext, Tera};
 use config::Config;
 use errors::{Result, ResultExt};
 use front_matter::{split_section_content, SectionFrontMatter};
+use imageproc::Processor;
 use rendering::{render_content, Header, RenderContext};
 use utils::fs::{find_related_assets, read_file};
 use utils::site::get_reading_analytics;
@@ -14,7 +16,7 @@ use utils::templates::render_template;
 
 use content::file_info::FileInfo;
 use content::ser::SerializingSection;
-use library::Library;
+use library::{Backlink, Library};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Section {
@@ -46,11 +48,24 @@ pub struct Section {
     pub subsections: Vec<Key>,
     /// Toc made from the headers of the markdown file
     pub toc: Vec<Header>,
+    /// The external links of the markdown content, to be checked once everything is rendered
+    pub external_links: Vec<String>,
+    /// The content files this section links to, relative to the `content` directory
+    pub internal_links: Vec<String>,
+    /// The pages and sections linking to this section, filled once all the content is rendered
+    pub backlinks: Vec<Backlink>,
     /// How many words in the raw content
     pub word_count: Option<usize>,
     /// How long would it take to read the raw content.
     /// See `get_reading_analytics` on how it is calculated
     pub reading_time: Option<usize>,
+    /// The language of that section. Equal to the default lang if the user doesn't setup `languages` in config.
+    /// Corresponds to the lang in the _index.{lang}.md file scheme
+    pub lang: String,
+    /// Contains all the translated version of that section
+    pub translations: Vec<Key>,
+    /// When the file of that section was last modified, only set if `git_last_modified` is enabled
+    pub last_modified: Option<String>,
 }
 
 impl Section {
@@ -72,19 +87,41 @@ impl Section {
             ignored_pages: vec![],
             subsections: vec![],
             toc: vec![],
+            external_links: vec![],
+            internal_links: vec![],
+            backlinks: vec![],
             word_count: None,
             reading_time: None,
+            lang: String::new(),
+            translations: Vec::new(),
+            last_modified: None,
         }
     }
 
     pub fn parse(file_path: &Path, content: &str, config: &Config) -> Result<Section> {
         let (meta, content) = split_section_content(file_path, content)?;
         let mut section = Section::new(file_path, meta);
+        // The feed of the index section would overwrite the feed of the whole site
+        if section.is_index() && section.meta.generate_rss {
+            bail!(
+                "Section `{}` is an index section and cannot use `generate_rss`, use `generate_rss` in the config instead",
+                file_path.display()
+            );
+        }
+        section.lang = section.file.find_language(config)?;
         section.raw_content = content.clone();
         let (word_count, reading_time) = get_reading_analytics(&section.raw_content);
         section.word_count = Some(word_count);
         section.reading_time = Some(reading_time);
-        section.path = format!("{}/", section.file.components.join("/"));
+        section.path = if section.lang != config.default_language {
+            if section.file.components.is_empty() {
+                format!("{}/", section.lang)
+            } else {
+                format!("{}/{}/", section.lang, section.file.components.join("/"))
+            }
+        } else {
+            format!("{}/", section.file.components.join("/"))
+        };
         section.components = section
             .path
             .split('/')
@@ -147,6 +184,7 @@ impl Section {
         permalinks: &HashMap<String, String>,
         tera: &Tera,
         config: &Config,
+        imageproc: Option<&Mutex<Processor>>,
     ) -> Result<()> {
         let mut context = RenderContext::new(
             tera,
@@ -155,6 +193,8 @@ impl Section {
             permalinks,
             self.meta.insert_anchor_links,
         );
+        context.imageproc = imageproc;
+        context.current_page_dir = self.file.relative_dir();
 
         context.tera_context.insert("section", &SerializingSection::from_section_basic(self, None));
 
@@ -162,6 +202,8 @@ impl Section {
             .chain_err(|| format!("Failed to render content of {}", self.file.path.display()))?;
         self.content = res.body;
         self.toc = res.toc;
+        self.external_links = res.external_links;
+        self.internal_links = res.internal_links;
         Ok(())
     }
 
@@ -174,6 +216,7 @@ impl Section {
         context.insert("current_url", &self.permalink);
         context.insert("current_path", &self.path);
         context.insert("section", &self.to_serialized(library));
+        context.insert("lang", &self.lang);
 
         render_template(tpl_name, tera, &context, &config.theme)
             .chain_err(|| format!("Failed to render section '{}'", self.file.path.display()))
@@ -221,8 +264,14 @@ impl Default for Section {
             ignored_pages: vec![],
             subsections: vec![],
             toc: vec![],
+            external_links: vec![],
+            internal_links: vec![],
+            backlinks: vec![],
             reading_time: None,
             word_count: None,
+            lang: String::new(),
+            translations: Vec::new(),
+            last_modified: None,
         }
     }
 }
@@ -235,8 +284,10 @@ mod tests {
     use globset::{Glob, GlobSetBuilder};
     use tempfile::tempdir;
 
+    use std::path::Path;
+
     use super::Section;
-    use config::Config;
+    use config::{Config, Language};
 
     #[test]
     fn section_with_assets_gets_right_info() {
@@ -285,4 +336,51 @@ mod tests {
         assert_eq!(page.assets.len(), 1);
         assert_eq!(page.assets[0].file_name().unwrap().to_str(), Some("graph.jpg"));
     }
+
+    #[test]
+    fn can_specify_language_in_filename() {
+        let mut config = Config::default();
+        config.languages.push(Language { code: String::from("fr"), ..Language::default() });
+        let content = r#"
++++
++++
+Bonjour le monde"#
+            .to_string();
+        let res = Section::parse(Path::new("content/hello/nested/_index.fr.md"), &content, &config);
+        assert!(res.is_ok());
+        let section = res.unwrap();
+        assert_eq!(section.lang, "fr".to_string());
+        assert_eq!(section.permalink, "http://a-website.com/fr/hello/nested/");
+    }
+
+    #[test]
+    fn errors_on_feed_for_index_section() {
+        let content = r#"
++++
+generate_rss = true
++++
+Hello world"#
+            .to_string();
+        let res = Section::parse(Path::new("content/_index.md"), &content, &Config::default());
+        assert!(res.is_err());
+        let res =
+            Section::parse(Path::new("content/posts/_index.md"), &content, &Config::default());
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn can_make_links_to_translated_sections_without_double_trailing_slash() {
+        let mut config = Config::default();
+        config.languages.push(Language { code: String::from("fr"), ..Language::default() });
+        let content = r#"
++++
++++
+Bonjour le monde"#
+            .to_string();
+        let res = Section::parse(Path::new("content/_index.fr.md"), &content, &config);
+        assert!(res.is_ok());
+        let section = res.unwrap();
+        assert_eq!(section.lang, "fr".to_string());
+        assert_eq!(section.permalink, "http://a-website.com/fr/");
+    }
 }
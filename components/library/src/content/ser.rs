@@ -1,11 +1,150 @@
 //! What we are sending to the templates when rendering them
 use std::collections::HashMap;
 
+use slotmap::Key;
 use tera::{Map, Value};
 
 use content::{Page, Section};
-use library::Library;
+use library::{Backlink, Library};
 use rendering::Header;
+use utils::site::to_rfc3339;
+
+/// A link to the same content in another language
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TranslatedContent<'a> {
+    lang: &'a str,
+    permalink: &'a str,
+    title: &'a Option<String>,
+}
+
+impl<'a> TranslatedContent<'a> {
+    // copypaste eh, not worth creating an enum imo
+    pub fn find_all_sections(section: &'a Section, library: &'a Library) -> Vec<Self> {
+        let mut translations = vec![];
+
+        for key in &section.translations {
+            let other = library.get_section_by_key(*key);
+            translations.push(TranslatedContent {
+                lang: &other.lang,
+                permalink: &other.permalink,
+                title: &other.meta.title,
+            });
+        }
+
+        translations
+    }
+
+    pub fn find_all_pages(page: &'a Page, library: &'a Library) -> Vec<Self> {
+        let mut translations = vec![];
+
+        for key in &page.translations {
+            let other = library.get_page_by_key(*key);
+            translations.push(TranslatedContent {
+                lang: &other.lang,
+                permalink: &other.permalink,
+                title: &other.meta.title,
+            });
+        }
+
+        translations
+    }
+}
+
+/// The data of an ancestor section without its content and pages, enough for breadcrumbs
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AncestorSection<'a> {
+    relative_path: &'a str,
+    permalink: &'a str,
+    path: &'a str,
+    title: &'a Option<String>,
+    description: &'a Option<String>,
+    extra: &'a HashMap<String, Value>,
+}
+
+impl<'a> AncestorSection<'a> {
+    pub fn find_all(ancestors: &'a [Key], library: &'a Library) -> Vec<Self> {
+        ancestors
+            .iter()
+            .map(|k| {
+                let section = library.get_section_by_key(*k);
+                AncestorSection {
+                    relative_path: &section.file.relative,
+                    permalink: &section.permalink,
+                    path: &section.path,
+                    title: &section.meta.title,
+                    description: &section.meta.description,
+                    extra: &section.meta.extra,
+                }
+            })
+            .collect()
+    }
+}
+
+/// A page or section linking to the current content
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SerializedBacklink<'a> {
+    permalink: &'a str,
+    title: &'a Option<String>,
+}
+
+impl<'a> SerializedBacklink<'a> {
+    pub fn find_all(backlinks: &[Backlink], library: &'a Library) -> Vec<Self> {
+        backlinks
+            .iter()
+            .map(|link| match *link {
+                Backlink::Page(k) => {
+                    let page = library.get_page_by_key(k);
+                    SerializedBacklink { permalink: &page.permalink, title: &page.meta.title }
+                }
+                Backlink::Section(k) => {
+                    let section = library.get_section_by_key(k);
+                    SerializedBacklink { permalink: &section.permalink, title: &section.meta.title }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Where a page is in its series
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SeriesInfo<'a> {
+    name: &'a str,
+    /// The position of the page in the series, starting at 1
+    index: usize,
+    first: Box<SerializingPage<'a>>,
+    previous: Option<Box<SerializingPage<'a>>>,
+    next: Option<Box<SerializingPage<'a>>>,
+    /// All the pages of the series, in order
+    pages: Vec<SerializingPage<'a>>,
+}
+
+impl<'a> SeriesInfo<'a> {
+    pub fn find(page: &'a Page, library: &'a Library) -> Option<Self> {
+        let name = match page.meta.series {
+            Some(ref n) => n,
+            None => return None,
+        };
+        // Not in its series if it has expired
+        let position = page
+            .series
+            .iter()
+            .position(|k| library.get_page_by_key(*k).file.path == page.file.path)?;
+        let pages: Vec<_> = page
+            .series
+            .iter()
+            .map(|k| SerializingPage::from_page_basic(library.get_page_by_key(*k), Some(library)))
+            .collect();
+
+        Some(SeriesInfo {
+            name,
+            index: position + 1,
+            first: Box::new(pages[0].clone()),
+            previous: if position > 0 { Some(Box::new(pages[position - 1].clone())) } else { None },
+            next: pages.get(position + 1).map(|p| Box::new(p.clone())),
+            pages,
+        })
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct SerializingPage<'a> {
@@ -14,9 +153,15 @@ pub struct SerializingPage<'a> {
     permalink: &'a str,
     slug: &'a str,
     ancestors: Vec<String>,
+    ancestor_sections: Vec<AncestorSection<'a>>,
+    section: Option<Box<SerializingSection<'a>>>,
+    backlinks: Vec<SerializedBacklink<'a>>,
     title: &'a Option<String>,
     description: &'a Option<String>,
     date: &'a Option<String>,
+    updated: &'a Option<String>,
+    date_rfc3339: Option<String>,
+    updated_rfc3339: Option<String>,
     year: Option<i32>,
     month: Option<u32>,
     day: Option<u32>,
@@ -34,6 +179,13 @@ pub struct SerializingPage<'a> {
     heavier: Option<Box<SerializingPage<'a>>>,
     earlier: Option<Box<SerializingPage<'a>>>,
     later: Option<Box<SerializingPage<'a>>>,
+    previous: Option<Box<SerializingPage<'a>>>,
+    next: Option<Box<SerializingPage<'a>>>,
+    lang: &'a str,
+    translations: Vec<TranslatedContent<'a>>,
+    series: Option<SeriesInfo<'a>>,
+    related: Vec<SerializingPage<'a>>,
+    last_modified: &'a Option<String>,
 }
 
 impl<'a> SerializingPage<'a> {
@@ -60,15 +212,39 @@ impl<'a> SerializingPage<'a> {
         let later = page
             .later
             .map(|k| Box::new(Self::from_page_basic(pages.get(k).unwrap(), Some(library))));
+        let previous = page
+            .previous
+            .map(|k| Box::new(Self::from_page_basic(pages.get(k).unwrap(), Some(library))));
+        let next = page
+            .next
+            .map(|k| Box::new(Self::from_page_basic(pages.get(k).unwrap(), Some(library))));
         let ancestors = page
             .ancestors
             .iter()
             .map(|k| library.get_section_by_key(*k).file.relative.clone())
             .collect();
+        let translations = TranslatedContent::find_all_pages(page, library);
+        let series = SeriesInfo::find(page, library);
+        let related = page
+            .related
+            .iter()
+            .map(|k| Self::from_page_basic(pages.get(*k).unwrap(), Some(library)))
+            .collect();
+        let ancestor_sections = AncestorSection::find_all(&page.ancestors, library);
+        let backlinks = SerializedBacklink::find_all(&page.backlinks, library);
+        let section = page.ancestors.last().map(|k| {
+            Box::new(SerializingSection::from_section_basic(
+                library.get_section_by_key(*k),
+                Some(library),
+            ))
+        });
 
         SerializingPage {
             relative_path: &page.file.relative,
             ancestors,
+            ancestor_sections,
+            section,
+            backlinks,
             content: &page.content,
             permalink: &page.permalink,
             slug: &page.slug,
@@ -76,6 +252,9 @@ impl<'a> SerializingPage<'a> {
             description: &page.meta.description,
             extra: &page.meta.extra,
             date: &page.meta.date,
+            updated: &page.meta.updated,
+            date_rfc3339: page.meta.datetime_utc.map(to_rfc3339),
+            updated_rfc3339: page.meta.updated_datetime_utc.map(to_rfc3339),
             year,
             month,
             day,
@@ -92,6 +271,13 @@ impl<'a> SerializingPage<'a> {
             heavier,
             earlier,
             later,
+            previous,
+            next,
+            lang: &page.lang,
+            translations,
+            series,
+            related,
+            last_modified: &page.last_modified,
         }
     }
 
@@ -113,10 +299,18 @@ impl<'a> SerializingPage<'a> {
         } else {
             vec![]
         };
+        let translations = if let Some(lib) = library {
+            TranslatedContent::find_all_pages(page, lib)
+        } else {
+            vec![]
+        };
 
         SerializingPage {
             relative_path: &page.file.relative,
             ancestors,
+            ancestor_sections: vec![],
+            section: None,
+            backlinks: vec![],
             content: &page.content,
             permalink: &page.permalink,
             slug: &page.slug,
@@ -124,6 +318,9 @@ impl<'a> SerializingPage<'a> {
             description: &page.meta.description,
             extra: &page.meta.extra,
             date: &page.meta.date,
+            updated: &page.meta.updated,
+            date_rfc3339: page.meta.datetime_utc.map(to_rfc3339),
+            updated_rfc3339: page.meta.updated_datetime_utc.map(to_rfc3339),
             year,
             month,
             day,
@@ -140,6 +337,13 @@ impl<'a> SerializingPage<'a> {
             heavier: None,
             earlier: None,
             later: None,
+            previous: None,
+            next: None,
+            lang: &page.lang,
+            translations,
+            series: None,
+            related: vec![],
+            last_modified: &page.last_modified,
         }
     }
 }
@@ -150,6 +354,9 @@ pub struct SerializingSection<'a> {
     content: &'a str,
     permalink: &'a str,
     ancestors: Vec<String>,
+    ancestor_sections: Vec<AncestorSection<'a>>,
+    parent: Option<Box<SerializingSection<'a>>>,
+    backlinks: Vec<SerializedBacklink<'a>>,
     title: &'a Option<String>,
     description: &'a Option<String>,
     extra: &'a HashMap<String, Value>,
@@ -161,6 +368,9 @@ pub struct SerializingSection<'a> {
     assets: &'a [String],
     pages: Vec<SerializingPage<'a>>,
     subsections: Vec<&'a str>,
+    lang: &'a str,
+    translations: Vec<TranslatedContent<'a>>,
+    last_modified: &'a Option<String>,
 }
 
 impl<'a> SerializingSection<'a> {
@@ -181,10 +391,22 @@ impl<'a> SerializingSection<'a> {
             .iter()
             .map(|k| library.get_section_by_key(*k).file.relative.clone())
             .collect();
+        let translations = TranslatedContent::find_all_sections(section, library);
+        let ancestor_sections = AncestorSection::find_all(&section.ancestors, library);
+        let backlinks = SerializedBacklink::find_all(&section.backlinks, library);
+        let parent = section.ancestors.last().map(|k| {
+            Box::new(SerializingSection::from_section_basic(
+                library.get_section_by_key(*k),
+                Some(library),
+            ))
+        });
 
         SerializingSection {
             relative_path: &section.file.relative,
             ancestors,
+            ancestor_sections,
+            parent,
+            backlinks,
             content: &section.content,
             permalink: &section.permalink,
             title: &section.meta.title,
@@ -198,6 +420,9 @@ impl<'a> SerializingSection<'a> {
             assets: &section.serialized_assets,
             pages,
             subsections,
+            lang: &section.lang,
+            translations,
+            last_modified: &section.last_modified,
         }
     }
 
@@ -212,10 +437,18 @@ impl<'a> SerializingSection<'a> {
         } else {
             vec![]
         };
+        let translations = if let Some(lib) = library {
+            TranslatedContent::find_all_sections(section, lib)
+        } else {
+            vec![]
+        };
 
         SerializingSection {
             relative_path: &section.file.relative,
             ancestors,
+            ancestor_sections: vec![],
+            parent: None,
+            backlinks: vec![],
             content: &section.content,
             permalink: &section.permalink,
             title: &section.meta.title,
@@ -229,6 +462,9 @@ impl<'a> SerializingSection<'a> {
             assets: &section.serialized_assets,
             pages: vec![],
             subsections: vec![],
+            lang: &section.lang,
+            translations,
+            last_modified: &section.last_modified,
         }
     }
 }
@@ -1,3 +1,4 @@
+extern crate deunicode;
 extern crate serde;
 extern crate slug;
 extern crate tera;
@@ -19,6 +20,7 @@ extern crate toml;
 
 extern crate config;
 extern crate front_matter;
+extern crate imageproc;
 extern crate rendering;
 extern crate utils;
 #[macro_use]
@@ -33,7 +35,7 @@ mod taxonomies;
 pub use slotmap::{DenseSlotMap, Key};
 
 pub use content::{Page, Section, SerializingPage, SerializingSection};
-pub use library::Library;
+pub use library::{Backlink, Library};
 pub use pagination::Paginator;
 pub use sorting::sort_actual_pages_by_date;
-pub use taxonomies::{find_taxonomies, Taxonomy, TaxonomyItem};
+pub use taxonomies::{find_taxonomies, Taxonomy, TaxonomyItem, TermContent};
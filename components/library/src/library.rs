@@ -1,12 +1,26 @@
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+use chrono::NaiveDateTime;
 use slotmap::{DenseSlotMap, Key};
 
+use config::Config;
 use front_matter::SortBy;
 
 use content::{Page, Section};
-use sorting::{find_siblings, sort_pages_by_date, sort_pages_by_weight};
+use sorting::{
+    find_siblings, sort_pages_by_date, sort_pages_by_extra, sort_pages_by_text,
+    sort_pages_by_weight,
+};
+use taxonomies::slugify_term;
+
+/// A page or a section linking to some content with an internal link
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backlink {
+    Page(Key),
+    Section(Key),
+}
 
 /// Houses everything about pages and sections
 /// Think of it as a database where each page and section has an id (Key here)
@@ -79,14 +93,10 @@ impl Library {
 
     /// Find out the direct subsections of each subsection if there are some
     /// as well as the pages for each section
-    pub fn populate_sections(&mut self) {
-        let (root_path, index_path) = self
-            .sections
-            .values()
-            .find(|s| s.is_index())
-            .map(|s| (s.file.parent.clone(), s.file.path.clone()))
-            .unwrap();
-        let root_key = self.paths_to_sections[&index_path];
+    pub fn populate_sections(&mut self, config: &Config) {
+        let now = config.build_datetime();
+        let root_path =
+            self.sections.values().find(|s| s.is_index()).map(|s| s.file.parent.clone()).unwrap();
 
         // We are going to get both the ancestors and grandparents for each section in one go
         let mut ancestors: HashMap<PathBuf, Vec<_>> = HashMap::new();
@@ -99,7 +109,8 @@ impl Library {
 
             if let Some(ref grand_parent) = section.file.grand_parent {
                 subsections
-                    .entry(grand_parent.join("_index.md"))
+                    // Using the original filename to work for multi-lingual sections
+                    .entry(grand_parent.join(&section.file.filename))
                     .or_insert_with(|| vec![])
                     .push(section.file.path.clone());
             }
@@ -111,7 +122,12 @@ impl Library {
             }
 
             let mut path = root_path.clone();
-            // Index section is the first ancestor of every single section
+            // Index section of the same language is the first ancestor of every single section
+            let root_key = match self.paths_to_sections.get(&root_path.join(&section.file.filename))
+            {
+                Some(key) => *key,
+                None => self.paths_to_sections[&root_path.join("_index.md")],
+            };
             let mut parents = vec![root_key];
             for component in &section.file.components {
                 path = path.join(component);
@@ -119,7 +135,9 @@ impl Library {
                 if path == section.file.parent {
                     continue;
                 }
-                if let Some(section_key) = self.paths_to_sections.get(&path.join("_index.md")) {
+                if let Some(section_key) =
+                    self.paths_to_sections.get(&path.join(&section.file.filename))
+                {
                     parents.push(*section_key);
                 }
             }
@@ -127,13 +145,22 @@ impl Library {
         }
 
         for (key, page) in &mut self.pages {
-            let mut parent_section_path = page.file.parent.join("_index.md");
+            // Pages go in the section of the same language
+            let parent_filename = if page.lang.is_empty() || page.lang == config.default_language {
+                "_index.md".to_string()
+            } else {
+                format!("_index.{}.md", page.lang)
+            };
+            let mut parent_section_path = page.file.parent.join(&parent_filename);
             while let Some(section_key) = self.paths_to_sections.get(&parent_section_path) {
                 let parent_is_transparent;
                 // We need to get a reference to a section later so keep the scope of borrowing small
                 {
                     let mut section = self.sections.get_mut(*section_key).unwrap();
-                    section.pages.push(key);
+                    // Drafts and expired pages are still rendered but not listed
+                    if !page.is_draft() && !page.is_expired(now) {
+                        section.pages.push(key);
+                    }
                     parent_is_transparent = section.meta.transparent;
                 }
                 page.ancestors =
@@ -158,14 +185,17 @@ impl Library {
                     break;
                 }
 
-                // We've added `_index.md` so if we are here so we need to go up twice
+                // We've added `_index(.{LANG})?.md` so if we are here so we need to go up twice
                 match parent_section_path.clone().parent().unwrap().parent() {
-                    Some(parent) => parent_section_path = parent.join("_index.md"),
+                    Some(parent) => parent_section_path = parent.join(&parent_filename),
                     None => break,
                 }
             }
         }
 
+        self.populate_translations();
+        self.populate_series(now);
+        self.populate_related_pages(config, now);
         self.sort_sections_pages();
 
         let sections = self.paths_to_sections.clone();
@@ -185,47 +215,256 @@ impl Library {
         }
     }
 
+    /// Find all the translated content for pages and sections
+    fn populate_translations(&mut self) {
+        let mut sections_translations = HashMap::new();
+        for (key, section) in &self.sections {
+            sections_translations
+                .entry(section.file.canonical.clone())
+                .or_insert_with(Vec::new)
+                .push(key);
+        }
+
+        for (key, section) in self.sections.iter_mut() {
+            let translations = &sections_translations[&section.file.canonical];
+            section.translations = translations.iter().filter(|k| **k != key).cloned().collect();
+        }
+
+        let mut pages_translations = HashMap::new();
+        for (key, page) in &self.pages {
+            pages_translations
+                .entry(page.file.canonical.clone())
+                .or_insert_with(Vec::new)
+                .push(key);
+        }
+
+        for (key, page) in self.pages.iter_mut() {
+            let translations = &pages_translations[&page.file.canonical];
+            page.translations = translations.iter().filter(|k| **k != key).cloned().collect();
+        }
+    }
+
+    /// Find all the pages and sections linking to each page and section, sorted by permalink.
+    /// The internal links are only known once the markdown is rendered so this needs to be
+    /// called after rendering
+    pub fn populate_backlinks(&mut self) {
+        let mut backlinks: HashMap<String, Vec<Backlink>> = HashMap::new();
+        for (key, page) in &self.pages {
+            for link in &page.internal_links {
+                if *link != page.file.relative {
+                    backlinks.entry(link.clone()).or_default().push(Backlink::Page(key));
+                }
+            }
+        }
+        for (key, section) in &self.sections {
+            for link in &section.internal_links {
+                if *link != section.file.relative {
+                    backlinks.entry(link.clone()).or_default().push(Backlink::Section(key));
+                }
+            }
+        }
+
+        for links in backlinks.values_mut() {
+            let (pages, sections) = (&self.pages, &self.sections);
+            links.sort_by(|a, b| {
+                let permalink = |link: &Backlink| match *link {
+                    Backlink::Page(k) => &pages[k].permalink,
+                    Backlink::Section(k) => &sections[k].permalink,
+                };
+                permalink(a).cmp(permalink(b))
+            });
+            links.dedup();
+        }
+
+        for page in self.pages.values_mut() {
+            page.backlinks = backlinks.get(&page.file.relative).cloned().unwrap_or_default();
+        }
+        for section in self.sections.values_mut() {
+            section.backlinks = backlinks.get(&section.file.relative).cloned().unwrap_or_default();
+        }
+    }
+
+    /// Find the related pages of each page: pages of the same language sharing taxonomy terms
+    /// with it, scored by the weight of the taxonomy of each shared term and by their date
+    fn populate_related_pages(&mut self, config: &Config, now: NaiveDateTime) {
+        for page in self.pages.values_mut() {
+            page.related = vec![];
+        }
+        let settings = &config.related_pages;
+        if !settings.is_enabled() {
+            return;
+        }
+
+        // (lang, taxonomy, term slug) -> pages with that term
+        let mut terms: HashMap<(&str, &str, String), Vec<Key>> = HashMap::new();
+        // The terms of each page, to only look at the pages sharing at least one of them
+        let mut pages_terms = Vec::new();
+        for (key, page) in &self.pages {
+            // Drafts and expired pages are still rendered but not listed
+            if page.is_draft() || page.is_expired(now) {
+                continue;
+            }
+            let mut page_terms = vec![];
+            for (taxonomy, values) in &page.meta.taxonomies {
+                if !settings.taxonomies.contains_key(taxonomy) {
+                    continue;
+                }
+                let kind = match config.taxonomies.iter().find(|t| t.name == *taxonomy) {
+                    Some(kind) => kind,
+                    None => continue,
+                };
+                let mut slugs: Vec<_> = values.iter().map(|v| slugify_term(v, kind)).collect();
+                slugs.sort();
+                slugs.dedup();
+                for slug in slugs {
+                    let term = (page.lang.as_str(), taxonomy.as_str(), slug);
+                    terms.entry(term.clone()).or_default().push(key);
+                    page_terms.push(term);
+                }
+            }
+            if !page_terms.is_empty() {
+                pages_terms.push((key, page_terms));
+            }
+        }
+
+        // The recency of a page goes from 0 for the oldest page to 1 for the newest one
+        let timestamps: Vec<_> =
+            self.pages.values().filter_map(|p| p.meta.datetime).map(|d| d.timestamp()).collect();
+        let oldest = timestamps.iter().min().cloned().unwrap_or(0);
+        let newest = timestamps.iter().max().cloned().unwrap_or(0);
+        let recency = |page: &Page| match page.meta.datetime {
+            Some(d) if newest > oldest => {
+                (d.timestamp() - oldest) as f64 / (newest - oldest) as f64
+            }
+            _ => 0.0,
+        };
+
+        let mut related = Vec::with_capacity(pages_terms.len());
+        for (key, page_terms) in pages_terms {
+            let mut scores: HashMap<Key, f64> = HashMap::new();
+            for term in &page_terms {
+                let weight = settings.taxonomies[term.1];
+                for other in terms[term].iter().filter(|k| **k != key) {
+                    *scores.entry(*other).or_insert(0.0) += weight;
+                }
+            }
+
+            let mut candidates: Vec<_> = scores
+                .into_iter()
+                .filter(|(_, score)| *score > 0.0)
+                .map(|(k, score)| {
+                    let page = &self.pages[k];
+                    (k, score + settings.recency_weight * recency(page), page)
+                })
+                .collect();
+            // Highest score first, then newest first and by permalink to be deterministic
+            candidates.sort_by(|a, b| {
+                b.1.partial_cmp(&a.1)
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| b.2.meta.datetime.cmp(&a.2.meta.datetime))
+                    .then_with(|| a.2.permalink.cmp(&b.2.permalink))
+            });
+            candidates.truncate(settings.limit);
+            related.push((key, candidates.into_iter().map(|(k, _, _)| k).collect()));
+        }
+
+        for (key, keys) in related {
+            self.pages.get_mut(key).unwrap().related = keys;
+        }
+    }
+
+    /// Find all the pages of each series, per language, sorted by part and then by date
+    fn populate_series(&mut self, now: NaiveDateTime) {
+        let mut series = HashMap::new();
+        for (key, page) in &self.pages {
+            // Drafts and expired pages are still rendered but not listed
+            if page.is_draft() || page.is_expired(now) {
+                continue;
+            }
+            if let Some(ref name) = page.meta.series {
+                series.entry((page.lang.clone(), name.clone())).or_insert_with(Vec::new).push(key);
+            }
+        }
+
+        for keys in series.values_mut() {
+            let pages = &self.pages;
+            keys.sort_by_key(|k| {
+                let page = &pages[*k];
+                (
+                    page.meta.series_part.is_none(),
+                    page.meta.series_part,
+                    page.meta.datetime.is_none(),
+                    page.meta.datetime,
+                    page.permalink.clone(),
+                )
+            });
+        }
+
+        for page in self.pages.values_mut() {
+            page.series = vec![];
+        }
+        for keys in series.values() {
+            for key in keys {
+                self.pages.get_mut(*key).unwrap().series = keys.clone();
+            }
+        }
+    }
+
     /// Sort all sections pages
     pub fn sort_sections_pages(&mut self) {
         let mut updates = HashMap::new();
         for (key, section) in &self.sections {
-            let (sorted_pages, cannot_be_sorted_pages) = match section.meta.sort_by {
-                SortBy::None => continue,
-                SortBy::Date => {
-                    let data = section
-                        .pages
-                        .iter()
-                        .map(|k| {
-                            if let Some(page) = self.pages.get(*k) {
-                                (k, page.meta.datetime, page.permalink.as_ref())
-                            } else {
-                                unreachable!("Sorting got an unknown page")
-                            }
-                        })
-                        .collect();
-
-                    sort_pages_by_date(data)
+            let pages = section.pages.iter().map(|k| {
+                if let Some(page) = self.pages.get(*k) {
+                    (k, page)
+                } else {
+                    unreachable!("Sorting got an unknown page")
                 }
-                SortBy::Weight => {
-                    let data = section
-                        .pages
-                        .iter()
-                        .map(|k| {
-                            if let Some(page) = self.pages.get(*k) {
-                                (k, page.meta.weight, page.permalink.as_ref())
-                            } else {
-                                unreachable!("Sorting got an unknown page")
-                            }
+            });
+            let (mut sorted_pages, cannot_be_sorted_pages) = match section.meta.sort_by {
+                SortBy::None => continue,
+                SortBy::Date => sort_pages_by_date(
+                    pages.map(|(k, p)| (k, p.meta.datetime, p.permalink.as_ref())).collect(),
+                ),
+                SortBy::Weight => sort_pages_by_weight(
+                    pages.map(|(k, p)| (k, p.meta.weight, p.permalink.as_ref())).collect(),
+                ),
+                SortBy::Title => sort_pages_by_text(
+                    pages
+                        .map(|(k, p)| {
+                            (k, p.meta.title.as_ref().map(|s| s.as_str()), p.permalink.as_ref())
                         })
-                        .collect();
-
-                    sort_pages_by_weight(data)
+                        .collect(),
+                ),
+                SortBy::Slug => sort_pages_by_text(
+                    pages.map(|(k, p)| (k, Some(p.slug.as_ref()), p.permalink.as_ref())).collect(),
+                ),
+                SortBy::Extra => {
+                    // Checked when parsing the front matter
+                    let field = section.meta.sort_by_extra.as_ref().unwrap();
+                    sort_pages_by_extra(
+                        pages
+                            .map(|(k, p)| (k, p.meta.extra.get(field), p.permalink.as_ref()))
+                            .collect(),
+                    )
                 }
             };
-            updates.insert(key, (sorted_pages, cannot_be_sorted_pages, section.meta.sort_by));
+            // Pages that can't be sorted stay at the end
+            if section.meta.reverse_sort {
+                sorted_pages.reverse();
+            }
+            updates.insert(
+                key,
+                (
+                    sorted_pages,
+                    cannot_be_sorted_pages,
+                    section.meta.sort_by,
+                    section.meta.reverse_sort,
+                ),
+            );
         }
 
-        for (key, (sorted, cannot_be_sorted, sort_by)) in updates {
+        for (key, (sorted, cannot_be_sorted, sort_by, reversed)) in updates {
             // Find sibling between sorted pages first
             let with_siblings = find_siblings(
                 sorted
@@ -241,6 +480,9 @@ impl Library {
             );
 
             for (k2, val1, val2) in with_siblings {
+                // The siblings keep their meaning when the order is reversed, eg `earlier`
+                // is always the previous page by date
+                let (val1, val2) = if reversed { (val2, val1) } else { (val1, val2) };
                 if let Some(page) = self.pages.get_mut(k2) {
                     match sort_by {
                         SortBy::Date => {
@@ -251,6 +493,10 @@ impl Library {
                             page.lighter = val1;
                             page.heavier = val2;
                         }
+                        SortBy::Title | SortBy::Slug | SortBy::Extra => {
+                            page.previous = val1;
+                            page.next = val2;
+                        }
                         SortBy::None => unreachable!("Impossible to find siblings in SortBy::None"),
                     }
                 } else {
@@ -266,6 +512,7 @@ impl Library {
     }
 
     /// Find all the orphan pages: pages that are in a folder without an `_index.md`
+    /// as well as expired pages, which are not listed in their section anymore
     pub fn get_all_orphan_pages(&self) -> Vec<&Page> {
         let pages_in_sections =
             self.sections.values().flat_map(|s| &s.pages).collect::<HashSet<_>>();
@@ -352,3 +599,168 @@ impl Library {
         self.paths_to_pages.contains_key(path.as_ref())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::DateTime;
+
+    use config::{Config, Taxonomy as TaxonomyConfig};
+    use content::{Page, Section};
+    use front_matter::{PageFrontMatter, SectionFrontMatter, SortBy};
+
+    use super::Library;
+
+    fn create_library(sort_by: SortBy, reverse_sort: bool, pages: &[(&str, &str)]) -> Library {
+        let mut library = Library::new(pages.len(), 2);
+        library.insert_section(Section::new("content/_index.md", SectionFrontMatter::default()));
+        let meta = SectionFrontMatter { sort_by, reverse_sort, ..SectionFrontMatter::default() };
+        library.insert_section(Section::new("content/posts/_index.md", meta));
+
+        for (filename, date) in pages {
+            let mut meta = PageFrontMatter::default();
+            meta.title = Some(filename.to_string());
+            meta.date = Some(date.to_string());
+            meta.date_to_datetime();
+            library.insert_page(Page::new(format!("content/posts/{}.md", filename), meta));
+        }
+        library.populate_sections(&Config::default());
+        library
+    }
+
+    fn section_titles(library: &Library) -> Vec<String> {
+        let section = library.get_section("content/posts/_index.md").unwrap();
+        section
+            .pages
+            .iter()
+            .map(|k| library.get_page_by_key(*k).meta.title.clone().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn can_sort_section_pages_by_title() {
+        let library = create_library(
+            SortBy::Title,
+            false,
+            &[("Part 10", "2018-01-01"), ("part 2", "2018-01-02"), ("Intro", "2018-01-03")],
+        );
+        assert_eq!(section_titles(&library), vec!["Intro", "part 2", "Part 10"]);
+    }
+
+    #[test]
+    fn can_reverse_sort_of_section_pages() {
+        let library = create_library(
+            SortBy::Date,
+            true,
+            &[("b", "2018-01-02"), ("a", "2018-01-01"), ("c", "2018-01-03")],
+        );
+        // Oldest first
+        assert_eq!(section_titles(&library), vec!["a", "b", "c"]);
+
+        // but the siblings still mean the same thing
+        let b = library.get_page("content/posts/b.md").unwrap();
+        let earlier = library.get_page_by_key(b.earlier.unwrap());
+        let later = library.get_page_by_key(b.later.unwrap());
+        assert_eq!(earlier.meta.title, Some("a".to_string()));
+        assert_eq!(later.meta.title, Some("c".to_string()));
+    }
+
+    #[test]
+    fn can_find_siblings_of_pages_sorted_by_title() {
+        let library = create_library(
+            SortBy::Title,
+            true,
+            &[("b", "2018-01-01"), ("a", "2018-01-02"), ("c", "2018-01-03")],
+        );
+        assert_eq!(section_titles(&library), vec!["c", "b", "a"]);
+
+        // previous/next follow the alphabetical order, even when reversed
+        let b = library.get_page("content/posts/b.md").unwrap();
+        let previous = library.get_page_by_key(b.previous.unwrap());
+        let next = library.get_page_by_key(b.next.unwrap());
+        assert_eq!(previous.meta.title, Some("a".to_string()));
+        assert_eq!(next.meta.title, Some("c".to_string()));
+        let a = library.get_page("content/posts/a.md").unwrap();
+        assert_eq!(a.previous, None);
+        assert_eq!(a.earlier, None);
+    }
+
+    fn related_titles(library: &Library, path: &str) -> Vec<String> {
+        let page = library.get_page(path).unwrap();
+        page.related
+            .iter()
+            .map(|k| library.get_page_by_key(*k).meta.title.clone().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn can_find_related_pages() {
+        let mut config = Config::default();
+        config.taxonomies = vec![
+            TaxonomyConfig { name: "tags".to_string(), ..TaxonomyConfig::default() },
+            TaxonomyConfig { name: "categories".to_string(), ..TaxonomyConfig::default() },
+        ];
+        config.related_pages.limit = 2;
+        config.related_pages.taxonomies.insert("tags".to_string(), 1.0);
+        config.related_pages.taxonomies.insert("categories".to_string(), 3.0);
+
+        let mut library = Library::new(5, 1);
+        library.insert_section(Section::new("content/_index.md", SectionFrontMatter::default()));
+        let pages = vec![
+            ("a", "2018-01-01", vec!["rust", "web"], "code"),
+            ("b", "2018-01-02", vec!["Rust", "web"], "life"),
+            ("c", "2018-01-03", vec!["python"], "code"),
+            ("d", "2018-01-04", vec!["rust"], "life"),
+            ("e", "2018-01-05", vec!["cooking"], "food"),
+        ];
+        for (title, date, tags, category) in pages {
+            let mut taxonomies = HashMap::new();
+            taxonomies.insert("tags".to_string(), tags.iter().map(|t| t.to_string()).collect());
+            taxonomies.insert("categories".to_string(), vec![category.to_string()]);
+            let mut meta = PageFrontMatter {
+                title: Some(title.to_string()),
+                date: Some(date.to_string()),
+                taxonomies,
+                ..PageFrontMatter::default()
+            };
+            meta.date_to_datetime();
+            library.insert_page(Page::new(format!("content/{}.md", title), meta));
+        }
+        library.populate_sections(&config);
+
+        // The shared category is worth more than the two shared tags
+        assert_eq!(related_titles(&library, "content/a.md"), vec!["c", "b"]);
+        // Sharing both a tag and the category beats sharing a tag with a newer page
+        assert_eq!(related_titles(&library, "content/d.md"), vec!["b", "a"]);
+        assert!(related_titles(&library, "content/e.md").is_empty());
+    }
+
+    #[test]
+    fn expires_pages_compared_to_the_build_time() {
+        let mut config = Config::default();
+        // 2018-01-02T00:00:00Z
+        config.build_timestamp = Some(1_514_851_200);
+
+        let mut library = Library::new(2, 1);
+        library.insert_section(Section::new("content/_index.md", SectionFrontMatter::default()));
+        for (title, expires) in &[("a", "2018-01-02T01:00:00+02:00"), ("b", "2018-01-02T01:00:00Z")]
+        {
+            let mut meta = PageFrontMatter::default();
+            meta.title = Some(title.to_string());
+            meta.expires_datetime =
+                DateTime::parse_from_rfc3339(expires).ok().map(|d| d.naive_utc());
+            library.insert_page(Page::new(format!("content/{}.md", title), meta));
+        }
+        library.populate_sections(&config);
+
+        let section = library.get_section("content/_index.md").unwrap();
+        let titles: Vec<_> = section
+            .pages
+            .iter()
+            .map(|k| library.get_page_by_key(*k).meta.title.clone().unwrap())
+            .collect();
+        // `a` expired at 23:00 UTC the day before
+        assert_eq!(titles, vec!["b"]);
+    }
+}
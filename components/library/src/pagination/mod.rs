@@ -144,7 +144,8 @@ impl<'a> Paginator<'a> {
             let permalink = format!("{}{}", self.permalink, page_path);
 
             let pager_path = if self.is_index {
-                page_path
+                // Index sections of other languages than the default one live in `{LANG}/`
+                format!("{}{}", self.path.trim_left_matches('/'), page_path)
             } else if self.path.ends_with('/') {
                 format!("{}{}", self.path, page_path)
             } else {
@@ -211,9 +212,11 @@ impl<'a> Paginator<'a> {
             PaginationRoot::Section(s) => {
                 context
                     .insert("section", &SerializingSection::from_section_basic(s, Some(library)));
+                context.insert("lang", &s.lang);
             }
             PaginationRoot::Taxonomy(t) => {
                 context.insert("taxonomy", &t.kind);
+                context.insert("lang", &config.default_language);
             }
         };
         context.insert("current_url", &pager.permalink);
@@ -233,7 +236,7 @@ mod tests {
     use content::{Page, Section};
     use front_matter::SectionFrontMatter;
     use library::Library;
-    use taxonomies::{Taxonomy, TaxonomyItem};
+    use taxonomies::{Taxonomy, TaxonomyItem, TermContent};
 
     use super::Paginator;
 
@@ -334,11 +337,17 @@ mod tests {
         };
         let taxonomy_item = TaxonomyItem {
             name: "Something".to_string(),
+            path: "Something".to_string(),
             slug: "something".to_string(),
             permalink: "https://vincent.is/tags/something/".to_string(),
             pages: library.pages().keys().collect(),
+            variants: vec![],
+            content: TermContent::default(),
+            parent: None,
+            children: vec![],
         };
-        let taxonomy = Taxonomy { kind: taxonomy_def, items: vec![taxonomy_item.clone()] };
+        let slugs = vec![(taxonomy_item.slug.clone(), 0)].into_iter().collect();
+        let taxonomy = Taxonomy { kind: taxonomy_def, items: vec![taxonomy_item.clone()], slugs };
         let paginator = Paginator::from_taxonomy(&taxonomy, &taxonomy_item, &library);
         assert_eq!(paginator.pagers.len(), 2);
 
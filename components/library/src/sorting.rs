@@ -1,8 +1,10 @@
 use std::cmp::Ordering;
 
 use chrono::NaiveDateTime;
+use deunicode::deunicode;
 use rayon::prelude::*;
 use slotmap::Key;
+use tera::Value;
 
 use content::Page;
 
@@ -55,8 +57,98 @@ pub fn sort_pages_by_weight(pages: Vec<(&Key, Option<usize>, &str)>) -> (Vec<Key
     (can_be_sorted.iter().map(|p| *p.0).collect(), cannot_be_sorted.iter().map(|p| *p.0).collect())
 }
 
-/// Find the lighter/heavier and earlier/later pages for all pages having a date/weight
-/// and that are not drafts.
+/// Compares two strings the way a human would: case and accents are ignored and numbers
+/// are compared by value so `Part 2` comes before `Part 10`
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let a_folded = deunicode(a).to_lowercase();
+    let b_folded = deunicode(b).to_lowercase();
+    let mut a_chars = a_folded.chars().peekable();
+    let mut b_chars = b_folded.chars().peekable();
+
+    loop {
+        let ord = match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => break,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let mut x_digits = String::new();
+                while let Some(c) = a_chars.peek().filter(|c| c.is_ascii_digit()) {
+                    x_digits.push(*c);
+                    a_chars.next();
+                }
+                let mut y_digits = String::new();
+                while let Some(c) = b_chars.peek().filter(|c| c.is_ascii_digit()) {
+                    y_digits.push(*c);
+                    b_chars.next();
+                }
+                let x_num = x_digits.trim_left_matches('0');
+                let y_num = y_digits.trim_left_matches('0');
+                x_num.len().cmp(&y_num.len()).then_with(|| x_num.cmp(y_num))
+            }
+            (Some(x), Some(y)) => {
+                let ord = x.cmp(y);
+                a_chars.next();
+                b_chars.next();
+                ord
+            }
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+
+    // Only differ by case or accents, still needs to be deterministic
+    a.cmp(b)
+}
+
+/// Takes a list of (page key, text like a title or slug, permalink) and sort them
+/// in natural order if possible
+/// Pages without text will be put in the unsortable bucket
+/// The permalink is used to break ties
+pub fn sort_pages_by_text(pages: Vec<(&Key, Option<&str>, &str)>) -> (Vec<Key>, Vec<Key>) {
+    let (mut can_be_sorted, cannot_be_sorted): (Vec<_>, Vec<_>) =
+        pages.into_par_iter().partition(|page| page.1.is_some());
+
+    can_be_sorted.par_sort_unstable_by(|a, b| {
+        let ord = natural_cmp(a.1.unwrap(), b.1.unwrap());
+        if ord == Ordering::Equal {
+            a.2.cmp(b.2)
+        } else {
+            ord
+        }
+    });
+
+    (can_be_sorted.iter().map(|p| *p.0).collect(), cannot_be_sorted.iter().map(|p| *p.0).collect())
+}
+
+/// Takes a list of (page key, extra value, permalink) and sort them by that value if possible:
+/// numbers first by value and then strings in natural order
+/// Pages without a number or string value will be put in the unsortable bucket
+/// The permalink is used to break ties
+pub fn sort_pages_by_extra(pages: Vec<(&Key, Option<&Value>, &str)>) -> (Vec<Key>, Vec<Key>) {
+    let (mut can_be_sorted, cannot_be_sorted): (Vec<_>, Vec<_>) = pages
+        .into_par_iter()
+        .partition(|page| page.1.map_or(false, |v| v.is_number() || v.is_string()));
+
+    can_be_sorted.par_sort_unstable_by(|a, b| {
+        let ord = match (a.1.unwrap(), b.1.unwrap()) {
+            (Value::String(x), Value::String(y)) => natural_cmp(x, y),
+            (Value::String(_), _) => Ordering::Greater,
+            (_, Value::String(_)) => Ordering::Less,
+            (x, y) => x.as_f64().partial_cmp(&y.as_f64()).unwrap_or(Ordering::Equal),
+        };
+        if ord == Ordering::Equal {
+            a.2.cmp(b.2)
+        } else {
+            ord
+        }
+    });
+
+    (can_be_sorted.iter().map(|p| *p.0).collect(), cannot_be_sorted.iter().map(|p| *p.0).collect())
+}
+
+/// Find the lighter/heavier, earlier/later or previous/next pages for all pages that
+/// can be sorted and that are not drafts.
 pub fn find_siblings(sorted: Vec<(&Key, bool)>) -> Vec<(Key, Option<Key>, Option<Key>)> {
     let mut res = Vec::with_capacity(sorted.len());
     let length = sorted.len();
@@ -114,7 +206,13 @@ pub fn find_siblings(sorted: Vec<(&Key, bool)>) -> Vec<(Key, Option<Key>, Option
 mod tests {
     use slotmap::DenseSlotMap;
 
-    use super::{find_siblings, sort_pages_by_date, sort_pages_by_weight};
+    use std::cmp::Ordering;
+    use tera::to_value;
+
+    use super::{
+        find_siblings, natural_cmp, sort_pages_by_date, sort_pages_by_extra, sort_pages_by_text,
+        sort_pages_by_weight,
+    };
     use content::Page;
     use front_matter::PageFrontMatter;
 
@@ -175,6 +273,68 @@ mod tests {
         assert_eq!(pages[2], key2);
     }
 
+    #[test]
+    fn can_sort_by_title() {
+        let mut dense = DenseSlotMap::new();
+        let mut page1 = create_page_with_weight(1);
+        page1.meta.title = Some("beta".to_string());
+        let key1 = dense.insert(page1.clone());
+        let mut page2 = create_page_with_weight(2);
+        page2.meta.title = Some("Gamma".to_string());
+        let key2 = dense.insert(page2.clone());
+        let mut page3 = create_page_with_weight(3);
+        page3.meta.title = Some("Alpha".to_string());
+        let key3 = dense.insert(page3.clone());
+
+        let input = vec![
+            (&key1, page1.meta.title.as_ref().map(|s| s.as_str()), page1.permalink.as_ref()),
+            (&key2, page2.meta.title.as_ref().map(|s| s.as_str()), page2.permalink.as_ref()),
+            (&key3, page3.meta.title.as_ref().map(|s| s.as_str()), page3.permalink.as_ref()),
+        ];
+        let (pages, _) = sort_pages_by_text(input);
+        // Should be sorted by title, whatever the case
+        assert_eq!(pages[0], key3);
+        assert_eq!(pages[1], key1);
+        assert_eq!(pages[2], key2);
+    }
+
+    #[test]
+    fn can_compare_naturally() {
+        assert_eq!(natural_cmp("Part 2", "part 10"), Ordering::Less);
+        assert_eq!(natural_cmp("Éclair", "ecole"), Ordering::Less);
+        assert_eq!(natural_cmp("file007", "file7"), Ordering::Less);
+        assert_eq!(natural_cmp("zebra", "Apple"), Ordering::Greater);
+        assert_eq!(natural_cmp("a", "a"), Ordering::Equal);
+    }
+
+    #[test]
+    fn can_sort_by_extra() {
+        let mut dense = DenseSlotMap::new();
+        let key1 = dense.insert(create_page_with_weight(1));
+        let key2 = dense.insert(create_page_with_weight(2));
+        let key3 = dense.insert(create_page_with_weight(3));
+        let key4 = dense.insert(create_page_with_weight(4));
+        let key5 = dense.insert(create_page_with_weight(5));
+        let (v1, v2, v3, v4) = (
+            to_value(10).unwrap(),
+            to_value(2.5).unwrap(),
+            to_value("b").unwrap(),
+            to_value(true).unwrap(),
+        );
+
+        let input = vec![
+            (&key1, Some(&v1), ""),
+            (&key2, Some(&v2), ""),
+            (&key3, Some(&v3), ""),
+            (&key4, Some(&v4), ""),
+            (&key5, None, ""),
+        ];
+        let (pages, unsorted) = sort_pages_by_extra(input);
+        // Numbers first, then strings
+        assert_eq!(pages, vec![key2, key1, key3]);
+        assert_eq!(unsorted, vec![key4, key5]);
+    }
+
     #[test]
     fn ignore_page_with_missing_field() {
         let mut dense = DenseSlotMap::new();
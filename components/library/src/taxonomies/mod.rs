@@ -2,26 +2,56 @@ use std::collections::HashMap;
 
 use slotmap::Key;
 use slug::slugify;
-use tera::{Context, Tera};
+use tera::{Context, Tera, Value};
 
-use config::{Config, Taxonomy as TaxonomyConfig};
+use config::{Config, PagesSortBy, Taxonomy as TaxonomyConfig, TermName, TermsSortBy};
 use errors::{Result, ResultExt};
 use utils::templates::render_template;
 
-use content::SerializingPage;
+use content::{Section, SerializingPage};
 use library::Library;
-use sorting::sort_pages_by_date;
+use sorting::{sort_pages_by_date, sort_pages_by_text, sort_pages_by_weight};
+
+/// The parent and children of a term in a hierarchical taxonomy
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct SerializedTermLink<'a> {
+    name: &'a str,
+    path: &'a str,
+    slug: &'a str,
+    permalink: &'a str,
+    page_count: usize,
+}
+
+impl<'a> SerializedTermLink<'a> {
+    fn find(slug: &str, taxonomy: &'a Taxonomy) -> Option<Self> {
+        taxonomy.get_item(slug).map(|item| SerializedTermLink {
+            name: &item.name,
+            path: &item.path,
+            slug: &item.slug,
+            permalink: &item.permalink,
+            page_count: item.pages.len(),
+        })
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
 struct SerializedTaxonomyItem<'a> {
     name: &'a str,
+    path: &'a str,
     slug: &'a str,
     permalink: &'a str,
+    page_count: usize,
+    parent: Option<SerializedTermLink<'a>>,
+    children: Vec<SerializedTermLink<'a>>,
+    title: Option<&'a str>,
+    description: Option<&'a str>,
+    content: &'a str,
+    extra: &'a HashMap<String, Value>,
     pages: Vec<SerializingPage<'a>>,
 }
 
 impl<'a> SerializedTaxonomyItem<'a> {
-    pub fn from_item(item: &'a TaxonomyItem, library: &'a Library) -> Self {
+    pub fn from_item(item: &'a TaxonomyItem, taxonomy: &'a Taxonomy, library: &'a Library) -> Self {
         let mut pages = vec![];
 
         for key in &item.pages {
@@ -31,45 +61,152 @@ impl<'a> SerializedTaxonomyItem<'a> {
 
         SerializedTaxonomyItem {
             name: &item.name,
+            path: &item.path,
             slug: &item.slug,
             permalink: &item.permalink,
+            page_count: item.pages.len(),
+            parent: item.parent.as_ref().and_then(|p| SerializedTermLink::find(p, taxonomy)),
+            children: item
+                .children
+                .iter()
+                .filter_map(|c| SerializedTermLink::find(c, taxonomy))
+                .collect(),
+            title: item.content.title.as_ref().map(|s| s.as_str()),
+            description: item.content.description.as_ref().map(|s| s.as_str()),
+            content: &item.content.content,
+            extra: &item.content.extra,
             pages,
         }
     }
 }
 
+/// What a term gets from its optional `content/{taxonomy}/{slug}/_index.md` file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TermContent {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// The HTML rendered from the markdown body
+    pub content: String,
+    pub extra: HashMap<String, Value>,
+}
+
+impl TermContent {
+    pub fn from_section(section: &Section) -> TermContent {
+        TermContent {
+            title: section.meta.title.clone(),
+            description: section.meta.description.clone(),
+            content: section.content.clone(),
+            extra: section.meta.extra.clone(),
+        }
+    }
+}
+
+/// Terms of hierarchical taxonomies are paths like `programming/rust` and each part
+/// of the path is slugified on its own
+pub fn slugify_term(name: &str, kind: &TaxonomyConfig) -> String {
+    if kind.hierarchical {
+        name.split('/').map(slugify).filter(|s| !s.is_empty()).collect::<Vec<_>>().join("/")
+    } else {
+        slugify(name)
+    }
+}
+
+/// Adds all the ancestors of the terms of a hierarchical taxonomy, eg `programming` for
+/// `programming/rust`, with the pages of their descendants if `include_descendants` is set
+fn add_parent_terms(
+    kind: &TaxonomyConfig,
+    items: HashMap<String, Vec<Key>>,
+) -> HashMap<String, Vec<Key>> {
+    let mut all_items: HashMap<String, Vec<Key>> = HashMap::new();
+
+    for (name, pages) in items {
+        let parts: Vec<_> = name.split('/').map(|p| p.trim()).filter(|p| !p.is_empty()).collect();
+        for i in 1..parts.len() {
+            let ancestor = all_items.entry(parts[..i].join("/")).or_default();
+            if kind.include_descendants {
+                ancestor.extend(pages.iter().cloned());
+            }
+        }
+        all_items.entry(parts.join("/")).or_default().extend(pages);
+    }
+
+    for pages in all_items.values_mut() {
+        pages.sort();
+        pages.dedup();
+    }
+
+    all_items
+}
+
 /// A taxonomy with all its pages
 #[derive(Debug, Clone, PartialEq)]
 pub struct TaxonomyItem {
+    /// The name of the term, only the last part of the path in a hierarchical taxonomy
     pub name: String,
+    /// The full name of the term, eg `Programming/Rust` in a hierarchical taxonomy.
+    /// Same as `name` otherwise
+    pub path: String,
     pub slug: String,
     pub permalink: String,
     pub pages: Vec<Key>,
+    /// The different names having this slug that were merged into this term, if any
+    pub variants: Vec<String>,
+    pub content: TermContent,
+    /// The slug of the parent term in a hierarchical taxonomy
+    pub parent: Option<String>,
+    /// The slugs of the child terms in a hierarchical taxonomy
+    pub children: Vec<String>,
 }
 
 impl TaxonomyItem {
-    pub fn new(name: &str, path: &str, config: &Config, keys: Vec<Key>, library: &Library) -> Self {
-        // Taxonomy are almost always used for blogs so we filter by dates
-        // and it's not like we can sort things across sections by anything other
-        // than dates
-        let data = keys
-            .iter()
-            .map(|k| {
-                if let Some(page) = library.pages().get(*k) {
-                    (k, page.meta.datetime, page.permalink.as_ref())
-                } else {
-                    unreachable!("Sorting got an unknown page")
-                }
-            })
-            .collect();
-        let (mut pages, ignored_pages) = sort_pages_by_date(data);
-        let slug = slugify(name);
-        let permalink = config.make_permalink(&format!("/{}/{}", path, slug));
+    pub fn new(
+        name: &str,
+        kind: &TaxonomyConfig,
+        config: &Config,
+        keys: Vec<Key>,
+        library: &Library,
+    ) -> Self {
+        // Taxonomy are almost always used for blogs so we sort by dates by default
+        // but things like series are better sorted by weight
+        let pages = keys.iter().map(|k| (k, library.get_page_by_key(*k)));
+        let (mut pages, ignored_pages) = match kind.sort_pages_by {
+            PagesSortBy::Weight => sort_pages_by_weight(
+                pages.map(|(k, p)| (k, p.meta.weight, p.permalink.as_ref())).collect(),
+            ),
+            PagesSortBy::Title => sort_pages_by_text(
+                pages
+                    .map(|(k, p)| {
+                        (k, p.meta.title.as_ref().map(|s| s.as_str()), p.permalink.as_ref())
+                    })
+                    .collect(),
+            ),
+            PagesSortBy::Date => sort_pages_by_date(
+                pages.map(|(k, p)| (k, p.meta.datetime, p.permalink.as_ref())).collect(),
+            ),
+        };
+        if kind.reverse_pages {
+            pages.reverse();
+        }
+        let slug = slugify_term(name, kind);
+        let permalink = config.make_permalink(&format!("/{}/{}", kind.name, slug));
 
-        // We still append pages without dates at the end
+        // We still append pages that couldn't be sorted at the end
         pages.extend(ignored_pages);
 
-        TaxonomyItem { name: name.to_string(), permalink, slug, pages }
+        // `add_parent_terms` already trimmed each part of the path
+        let leaf = if kind.hierarchical { name.rsplit('/').next().unwrap() } else { name };
+
+        TaxonomyItem {
+            name: leaf.to_string(),
+            path: name.to_string(),
+            permalink,
+            slug,
+            pages,
+            variants: vec![],
+            content: TermContent::default(),
+            parent: None,
+            children: vec![],
+        }
     }
 }
 
@@ -81,8 +218,11 @@ pub struct SerializedTaxonomy<'a> {
 
 impl<'a> SerializedTaxonomy<'a> {
     pub fn from_taxonomy(taxonomy: &'a Taxonomy, library: &'a Library) -> Self {
-        let items: Vec<SerializedTaxonomyItem> =
-            taxonomy.items.iter().map(|i| SerializedTaxonomyItem::from_item(i, library)).collect();
+        let items: Vec<SerializedTaxonomyItem> = taxonomy
+            .items
+            .iter()
+            .map(|i| SerializedTaxonomyItem::from_item(i, taxonomy, library))
+            .collect();
         SerializedTaxonomy { kind: &taxonomy.kind, items }
     }
 }
@@ -93,6 +233,8 @@ pub struct Taxonomy {
     pub kind: TaxonomyConfig,
     // this vec is sorted by the count of item
     pub items: Vec<TaxonomyItem>,
+    /// The index of each term in `items`, by slug
+    pub slugs: HashMap<String, usize>,
 }
 
 impl Taxonomy {
@@ -102,13 +244,93 @@ impl Taxonomy {
         items: HashMap<String, Vec<Key>>,
         library: &Library,
     ) -> Taxonomy {
-        let mut sorted_items = vec![];
+        let items = if kind.hierarchical { add_parent_terms(&kind, items) } else { items };
+
+        // Terms with the same slug would end up at the same URL so they are merged
+        let mut by_slug: HashMap<String, Vec<(String, Vec<Key>)>> = HashMap::new();
         for (name, pages) in items {
-            sorted_items.push(TaxonomyItem::new(&name, &kind.name, config, pages, library));
+            by_slug.entry(slugify_term(&name, &kind)).or_default().push((name, pages));
         }
-        sorted_items.sort_by(|a, b| a.name.cmp(&b.name));
 
-        Taxonomy { kind, items: sorted_items }
+        let mut sorted_items = vec![];
+        for (_, mut terms) in by_slug {
+            if terms.len() == 1 {
+                let (name, pages) = terms.pop().unwrap();
+                sorted_items.push(TaxonomyItem::new(&name, &kind, config, pages, library));
+                continue;
+            }
+
+            // Sort the names by the oldest page using them, pages without dates coming last
+            // and the permalink breaking ties so the choice doesn't depend on loading order
+            // Names without pages, like parents in hierarchical taxonomies, come last
+            terms.sort_by_key(|(_, pages)| {
+                let oldest = pages
+                    .iter()
+                    .map(|k| {
+                        let page = library.get_page_by_key(*k);
+                        (page.meta.datetime.is_none(), page.meta.datetime, page.permalink.clone())
+                    })
+                    .min();
+                (oldest.is_none(), oldest)
+            });
+            if kind.term_name == TermName::MostUsed {
+                // The sort is stable so ties are still broken by the oldest page
+                terms.sort_by_key(|(_, pages)| ::std::cmp::Reverse(pages.len()));
+            }
+
+            let name = terms[0].0.clone();
+            let mut variants: Vec<String> = terms.iter().map(|(n, _)| n.clone()).collect();
+            variants.sort();
+            let mut pages: Vec<Key> = terms.into_iter().flat_map(|(_, p)| p).collect();
+            // A page could be using several of those names
+            pages.sort();
+            pages.dedup();
+
+            let mut item = TaxonomyItem::new(&name, &kind, config, pages, library);
+            item.variants = variants;
+            sorted_items.push(item);
+        }
+        match kind.sort_terms_by {
+            TermsSortBy::PageCount => sorted_items.sort_by(|a, b| {
+                b.pages.len().cmp(&a.pages.len()).then_with(|| a.path.cmp(&b.path))
+            }),
+            TermsSortBy::Custom => {
+                // Terms can be listed by name or slug, the ones not listed come last
+                let position = |item: &TaxonomyItem| {
+                    kind.terms_order
+                        .iter()
+                        .position(|t| *t == item.path || *t == item.slug)
+                        .unwrap_or(kind.terms_order.len())
+                };
+                sorted_items.sort_by(|a, b| {
+                    position(a).cmp(&position(b)).then_with(|| a.path.cmp(&b.path))
+                });
+            }
+            TermsSortBy::Name => sorted_items.sort_by(|a, b| a.path.cmp(&b.path)),
+        }
+
+        if kind.hierarchical {
+            let mut children: HashMap<String, Vec<String>> = HashMap::new();
+            for item in &mut sorted_items {
+                if let Some(pos) = item.slug.rfind('/') {
+                    let parent = item.slug[..pos].to_string();
+                    children.entry(parent.clone()).or_default().push(item.slug.clone());
+                    item.parent = Some(parent);
+                }
+            }
+            for item in &mut sorted_items {
+                item.children = children.remove(&item.slug).unwrap_or_default();
+            }
+        }
+
+        let slugs = sorted_items.iter().enumerate().map(|(i, t)| (t.slug.clone(), i)).collect();
+
+        Taxonomy { kind, items: sorted_items, slugs }
+    }
+
+    /// Finds a term by its slug
+    pub fn get_item(&self, slug: &str) -> Option<&TaxonomyItem> {
+        self.slugs.get(slug).map(|i| &self.items[*i])
     }
 
     pub fn len(&self) -> usize {
@@ -128,13 +350,14 @@ impl Taxonomy {
     ) -> Result<String> {
         let mut context = Context::new();
         context.insert("config", config);
-        context.insert("term", &SerializedTaxonomyItem::from_item(item, library));
+        context.insert("term", &SerializedTaxonomyItem::from_item(item, self, library));
         context.insert("taxonomy", &self.kind);
         context.insert(
             "current_url",
             &config.make_permalink(&format!("{}/{}", self.kind.name, item.slug)),
         );
         context.insert("current_path", &format!("/{}/{}", self.kind.name, item.slug));
+        context.insert("lang", &config.default_language);
 
         render_template(&format!("{}/single.html", self.kind.name), tera, &context, &config.theme)
             .chain_err(|| format!("Failed to render single term {} page.", self.kind.name))
@@ -148,12 +371,16 @@ impl Taxonomy {
     ) -> Result<String> {
         let mut context = Context::new();
         context.insert("config", config);
-        let terms: Vec<SerializedTaxonomyItem> =
-            self.items.iter().map(|i| SerializedTaxonomyItem::from_item(i, library)).collect();
+        let terms: Vec<SerializedTaxonomyItem> = self
+            .items
+            .iter()
+            .map(|i| SerializedTaxonomyItem::from_item(i, self, library))
+            .collect();
         context.insert("terms", &terms);
         context.insert("taxonomy", &self.kind);
         context.insert("current_url", &config.make_permalink(&self.kind.name));
         context.insert("current_path", &self.kind.name);
+        context.insert("lang", &config.default_language);
 
         render_template(&format!("{}/list.html", self.kind.name), tera, &context, &config.theme)
             .chain_err(|| format!("Failed to render a list of {} page.", self.kind.name))
@@ -173,10 +400,11 @@ pub fn find_taxonomies(config: &Config, library: &Library) -> Result<Vec<Taxonom
         m
     };
     let mut all_taxonomies = HashMap::new();
+    let now = config.build_datetime();
 
     for (key, page) in library.pages() {
-        // Draft are not part of taxonomies
-        if page.is_draft() {
+        // Draft and expired pages are not part of taxonomies
+        if page.is_draft() || page.is_expired(now) {
             continue;
         }
 
@@ -216,10 +444,21 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
 
-    use config::{Config, Taxonomy as TaxonomyConfig};
+    use config::{Config, PagesSortBy, Taxonomy as TaxonomyConfig, TermName, TermsSortBy};
     use content::Page;
+    use front_matter::PageFrontMatter;
     use library::Library;
 
+    fn create_page_with_tags(date: &str, tags: &[&str]) -> Page {
+        let mut front_matter = PageFrontMatter::default();
+        front_matter.date = Some(date.to_string());
+        front_matter.date_to_datetime();
+        front_matter
+            .taxonomies
+            .insert("tags".to_string(), tags.iter().map(|t| t.to_string()).collect());
+        Page::new(format!("content/{}.md", date), front_matter)
+    }
+
     #[test]
     fn can_make_taxonomies() {
         let mut config = Config::default();
@@ -322,4 +561,123 @@ mod tests {
             "Page `` has taxonomy `tags` which is not defined in config.toml"
         );
     }
+
+    #[test]
+    fn merges_terms_with_the_same_slug() {
+        let mut config = Config::default();
+        let mut library = Library::new(3, 0);
+        config.taxonomies =
+            vec![TaxonomyConfig { name: "tags".to_string(), ..TaxonomyConfig::default() }];
+
+        library.insert_page(create_page_with_tags("2018-03-01", &["rust"]));
+        library.insert_page(create_page_with_tags("2018-01-01", &["Rust", "db"]));
+        library.insert_page(create_page_with_tags("2018-02-01", &["rust", "RUST"]));
+
+        let taxonomies = find_taxonomies(&config, &library).unwrap();
+        let tags = &taxonomies[0];
+        assert_eq!(tags.items.len(), 2);
+        assert_eq!(tags.items[0].name, "Rust");
+        assert_eq!(tags.items[0].slug, "rust");
+        assert_eq!(tags.items[0].pages.len(), 3);
+        assert_eq!(tags.items[0].variants, vec!["RUST", "Rust", "rust"]);
+        assert_eq!(tags.items[1].name, "db");
+        assert!(tags.items[1].variants.is_empty());
+    }
+
+    #[test]
+    fn can_keep_most_used_name_when_merging_terms() {
+        let mut config = Config::default();
+        let mut library = Library::new(3, 0);
+        config.taxonomies = vec![TaxonomyConfig {
+            name: "tags".to_string(),
+            term_name: TermName::MostUsed,
+            ..TaxonomyConfig::default()
+        }];
+
+        library.insert_page(create_page_with_tags("2018-03-01", &["rust"]));
+        library.insert_page(create_page_with_tags("2018-01-01", &["Rust"]));
+        library.insert_page(create_page_with_tags("2018-02-01", &["rust"]));
+
+        let taxonomies = find_taxonomies(&config, &library).unwrap();
+        let tags = &taxonomies[0];
+        assert_eq!(tags.items.len(), 1);
+        assert_eq!(tags.items[0].name, "rust");
+        assert_eq!(tags.items[0].pages.len(), 3);
+        assert_eq!(tags.items[0].variants, vec!["Rust", "rust"]);
+    }
+
+    #[test]
+    fn can_sort_pages_and_terms() {
+        let mut config = Config::default();
+        let mut library = Library::new(3, 0);
+        config.taxonomies = vec![TaxonomyConfig {
+            name: "tags".to_string(),
+            sort_pages_by: PagesSortBy::Weight,
+            reverse_pages: true,
+            sort_terms_by: TermsSortBy::PageCount,
+            ..TaxonomyConfig::default()
+        }];
+
+        let mut page1 = create_page_with_tags("2018-01-01", &["rust", "db"]);
+        page1.meta.weight = Some(1);
+        let key1 = library.insert_page(page1);
+        let mut page2 = create_page_with_tags("2018-02-01", &["rust", "db", "python"]);
+        page2.meta.weight = Some(2);
+        let key2 = library.insert_page(page2);
+        let key3 = library.insert_page(create_page_with_tags("2018-03-01", &["rust"]));
+
+        let taxonomies = find_taxonomies(&config, &library).unwrap();
+        let tags = &taxonomies[0];
+        assert_eq!(
+            tags.items.iter().map(|i| i.name.as_ref()).collect::<Vec<&str>>(),
+            vec!["rust", "db", "python"]
+        );
+        // The page without weight is still at the end
+        assert_eq!(tags.items[0].pages, vec![key2, key1, key3]);
+
+        config.taxonomies[0].sort_terms_by = TermsSortBy::Custom;
+        config.taxonomies[0].terms_order = vec!["python".to_string()];
+        let taxonomies = find_taxonomies(&config, &library).unwrap();
+        assert_eq!(
+            taxonomies[0].items.iter().map(|i| i.name.as_ref()).collect::<Vec<&str>>(),
+            vec!["python", "db", "rust"]
+        );
+    }
+
+    #[test]
+    fn can_make_hierarchical_taxonomies() {
+        let mut config = Config::default();
+        let mut library = Library::new(3, 0);
+        config.taxonomies = vec![TaxonomyConfig {
+            name: "tags".to_string(),
+            hierarchical: true,
+            ..TaxonomyConfig::default()
+        }];
+
+        library.insert_page(create_page_with_tags("2018-01-01", &["Programming/Rust"]));
+        library.insert_page(create_page_with_tags("2018-02-01", &["programming / python"]));
+        library.insert_page(create_page_with_tags("2018-03-01", &["Programming"]));
+
+        let taxonomies = find_taxonomies(&config, &library).unwrap();
+        let tags = &taxonomies[0];
+        assert_eq!(tags.items.len(), 3);
+        assert_eq!(tags.items[0].name, "Programming");
+        assert_eq!(tags.items[0].slug, "programming");
+        assert_eq!(tags.items[0].pages.len(), 1);
+        assert_eq!(tags.items[0].parent, None);
+        assert_eq!(tags.items[0].children, vec!["programming/rust", "programming/python"]);
+        assert_eq!(tags.items[1].name, "Rust");
+        assert_eq!(tags.items[1].path, "Programming/Rust");
+        assert_eq!(tags.items[1].slug, "programming/rust");
+        assert_eq!(tags.items[1].permalink, "http://a-website.com/tags/programming/rust/");
+        assert_eq!(tags.items[1].parent, Some("programming".to_string()));
+        assert_eq!(tags.items[2].name, "python");
+        assert_eq!(tags.items[2].path, "programming/python");
+        assert!(tags.items[2].children.is_empty());
+
+        config.taxonomies[0].include_descendants = true;
+        let taxonomies = find_taxonomies(&config, &library).unwrap();
+        assert_eq!(taxonomies[0].items[0].pages.len(), 3);
+        assert_eq!(taxonomies[0].items[1].pages.len(), 1);
+    }
 }
@@ -1,12 +1,24 @@
+extern crate percent_encoding;
+extern crate rayon;
 extern crate reqwest;
 #[macro_use]
 extern crate lazy_static;
 
-use reqwest::header::{HeaderMap, ACCEPT};
-use reqwest::StatusCode;
+extern crate config;
+extern crate errors;
+
+use percent_encoding::percent_decode;
+use rayon::prelude::*;
+use reqwest::header::{HeaderMap, ACCEPT, RETRY_AFTER};
+use reqwest::{Client, Method, StatusCode, Url};
+use std::cmp;
 use std::collections::HashMap;
-use std::error::Error;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use config::LinkChecker;
+use errors::{Result, ResultExt};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct LinkResult {
@@ -46,49 +58,261 @@ lazy_static! {
     static ref LINKS: Arc<RwLock<HashMap<String, LinkResult>>> = Arc::new(RwLock::new(HashMap::new()));
 }
 
-pub fn check_url(url: &str) -> LinkResult {
-    {
-        let guard = LINKS.read().unwrap();
-        if let Some(res) = guard.get(url) {
-            return res.clone();
+/// We never wait longer than that many seconds before retrying, whatever the server asks for
+const MAX_RETRY_DELAY_SECS: u64 = 30;
+
+/// The exponential backoff before a retry when the server doesn't say how long to wait
+fn retry_delay(attempt: u32) -> Duration {
+    let secs =
+        1u64.checked_shl(attempt).map_or(MAX_RETRY_DELAY_SECS, |s| s.min(MAX_RETRY_DELAY_SECS));
+    Duration::from_secs(secs)
+}
+
+/// How long to wait before a retry: what the server asked for if it did, capped
+/// to `MAX_RETRY_DELAY_SECS`
+fn retry_wait(retry_after: Option<Duration>, attempt: u32) -> Duration {
+    let delay = retry_after.unwrap_or_else(|| retry_delay(attempt));
+    cmp::min(delay, Duration::from_secs(MAX_RETRY_DELAY_SECS))
+}
+
+/// Only rate limiting and server errors are worth retrying
+fn should_retry(res: &LinkResult) -> bool {
+    match res.code {
+        Some(c) => c == StatusCode::TOO_MANY_REQUESTS || c.is_server_error(),
+        None => false,
+    }
+}
+
+/// Checks links concurrently, sharing a single HTTP client and
+/// making sure the same domain isn't hit too often
+struct Checker<'a> {
+    client: Client,
+    config: &'a LinkChecker,
+    /// When the next request to a given domain can be sent
+    next_request_at: Mutex<HashMap<String, Instant>>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(config: &'a LinkChecker) -> Result<Checker<'a>> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout))
+            .build()
+            .chain_err(|| "Failed to create the HTTP client of the link checker")?;
+
+        Ok(Checker { client, config, next_request_at: Mutex::new(HashMap::new()) })
+    }
+
+    fn check(&self, url: &str) -> LinkResult {
+        {
+            let guard = LINKS.read().unwrap();
+            if let Some(res) = guard.get(url) {
+                return res.clone();
+            }
         }
+
+        let mut attempt = 0;
+        let res = loop {
+            self.wait_for_domain(url);
+            let (res, retry_after) = self.request(url);
+            if !should_retry(&res) || attempt >= self.config.retries {
+                break res;
+            }
+
+            attempt += 1;
+            thread::sleep(retry_wait(retry_after, attempt));
+        };
+
+        LINKS.write().unwrap().insert(url.to_string(), res.clone());
+        res
     }
 
-    let mut headers = HeaderMap::new();
-    headers.insert(ACCEPT, "text/html".parse().unwrap());
-    headers.append(ACCEPT, "*/*".parse().unwrap());
+    /// Tries a HEAD request first since it is much cheaper but falls back to a GET
+    /// as some servers do not handle HEAD properly.
+    /// Links with an anchor are always fetched with a GET so the anchor can be looked for
+    /// in the page, unless it matches one of the `skip_anchor_prefixes`.
+    /// Also returns how long to wait before retrying if the server asked for it
+    fn request(&self, url: &str) -> (LinkResult, Option<Duration>) {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, "text/html".parse().unwrap());
+        headers.append(ACCEPT, "*/*".parse().unwrap());
 
-    let client = reqwest::Client::new();
+        let anchor = match url.find('#') {
+            Some(i) if !self.config.should_skip_anchor(url) => Some(&url[i + 1..]),
+            _ => None,
+        };
+        let methods: &[Method] =
+            if anchor.is_some() { &[Method::GET] } else { &[Method::HEAD, Method::GET] };
 
-    // Need to actually do the link checking
-    let res = match client.get(url).headers(headers).send() {
-        Ok(response) => LinkResult { code: Some(response.status()), error: None },
-        Err(e) => LinkResult { code: None, error: Some(e.description().to_string()) },
-    };
+        let mut res = (LinkResult { code: None, error: None }, None);
+        for method in methods {
+            res = match self.client.request(method.clone(), url).headers(headers.clone()).send() {
+                Ok(mut response) => {
+                    let retry_after = response
+                        .headers()
+                        .get(RETRY_AFTER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.parse::<u64>().ok())
+                        .map(Duration::from_secs);
+                    let code = Some(response.status());
+                    let error = match anchor {
+                        Some(anchor) if response.status().is_success() => match response.text() {
+                            Ok(ref body) if has_anchor(body, anchor) => None,
+                            Ok(_) => Some(format!("Anchor `#{}` not found", anchor)),
+                            Err(e) => Some(e.to_string()),
+                        },
+                        _ => None,
+                    };
+                    (LinkResult { code, error }, retry_after)
+                }
+                Err(e) => (LinkResult { code: None, error: Some(e.to_string()) }, None),
+            };
+            if res.0.is_valid() {
+                break;
+            }
+        }
+        res
+    }
+
+    /// Blocks until a request to the domain of that url can be sent
+    fn wait_for_domain(&self, url: &str) {
+        let domain = match Url::parse(url).ok().and_then(|u| u.host_str().map(|h| h.to_string())) {
+            Some(d) => d,
+            None => return,
+        };
+
+        let now = Instant::now();
+        let wait_until = {
+            let mut next_request_at = self.next_request_at.lock().unwrap();
+            let wait_until = match next_request_at.get(&domain) {
+                Some(at) if *at > now => *at,
+                _ => now,
+            };
+            next_request_at
+                .insert(domain, wait_until + Duration::from_millis(self.config.domain_delay));
+            wait_until
+        };
+
+        if wait_until > now {
+            thread::sleep(wait_until - now);
+        }
+    }
+}
 
-    LINKS.write().unwrap().insert(url.to_string(), res.clone());
-    res
+/// Whether the HTML page has an element with that anchor as `id` or `name`.
+/// The anchor is percent-decoded first and also looked for with the `user-content-` prefix
+/// GitHub adds to the ids of rendered markdown files
+fn has_anchor(body: &str, anchor: &str) -> bool {
+    let anchor = percent_decode(anchor.as_bytes()).decode_utf8_lossy();
+    let anchors = [anchor.to_string(), format!("user-content-{}", anchor)];
+    anchors.iter().any(|anchor| {
+        ["id", "name"].iter().any(|attr| {
+            body.contains(&format!("{}=\"{}\"", attr, anchor))
+                || body.contains(&format!("{}='{}'", attr, anchor))
+        })
+    })
+}
+
+/// Checks a single url
+pub fn check_url(url: &str, config: &LinkChecker) -> Result<LinkResult> {
+    Ok(Checker::new(config)?.check(url))
+}
+
+/// Checks all the given urls in parallel, with at most `config.concurrency` requests at the
+/// same time. The results are in the same order as the urls.
+pub fn check_urls(urls: &[String], config: &LinkChecker) -> Result<Vec<(String, LinkResult)>> {
+    let checker = Checker::new(config)?;
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.concurrency)
+        .build()
+        .chain_err(|| "Failed to create the thread pool of the link checker")?;
+
+    Ok(pool.install(|| urls.par_iter().map(|url| (url.clone(), checker.check(url))).collect()))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{check_url, LINKS};
+    use std::time::Duration;
+
+    use reqwest::StatusCode;
+
+    use super::{
+        check_url, check_urls, has_anchor, retry_delay, retry_wait, should_retry, LinkResult, LINKS,
+    };
+
+    use config::LinkChecker;
 
+    // The tests making requests need network access, run them with `cargo test -- --ignored`
     #[test]
+    #[ignore]
     fn can_validate_ok_links() {
         let url = "https://google.com";
-        let res = check_url(url);
+        let res = check_url(url, &LinkChecker::default()).unwrap();
         assert!(res.is_valid());
         assert!(LINKS.read().unwrap().get(url).is_some());
-        let res = check_url(url);
+        let res = check_url(url, &LinkChecker::default()).unwrap();
         assert!(res.is_valid());
     }
 
     #[test]
+    #[ignore]
     fn can_fail_404_links() {
-        let res = check_url("https://google.comys");
+        let res = check_url("https://google.comys", &LinkChecker::default()).unwrap();
         assert_eq!(res.is_valid(), false);
         assert!(res.code.is_none());
         assert!(res.error.is_some());
     }
+
+    #[test]
+    #[ignore]
+    fn can_check_several_links() {
+        let urls = vec!["https://google.comyz".to_string(), "https://google.comyx".to_string()];
+        let res =
+            check_urls(&urls, &LinkChecker { concurrency: 2, ..LinkChecker::default() }).unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].0, "https://google.comyz");
+        assert_eq!(res[1].0, "https://google.comyx");
+        assert!(res.iter().all(|(_, r)| !r.is_valid()));
+    }
+
+    #[test]
+    fn only_retries_rate_limited_and_server_errors() {
+        let result = |code| LinkResult { code: Some(code), error: None };
+        assert!(should_retry(&result(StatusCode::TOO_MANY_REQUESTS)));
+        assert!(should_retry(&result(StatusCode::SERVICE_UNAVAILABLE)));
+        assert!(!should_retry(&result(StatusCode::NOT_FOUND)));
+        assert!(!should_retry(&result(StatusCode::OK)));
+        assert!(!should_retry(&LinkResult { code: None, error: Some("DNS".to_string()) }));
+    }
+
+    #[test]
+    fn waits_what_the_server_asks_for_before_retrying() {
+        assert_eq!(retry_wait(Some(Duration::from_secs(5)), 1).as_secs(), 5);
+        assert_eq!(retry_wait(Some(Duration::from_secs(3600)), 1).as_secs(), 30);
+        assert_eq!(retry_wait(None, 2).as_secs(), 4);
+    }
+
+    #[test]
+    fn can_find_anchors() {
+        let body = r#"<h1 id="hello">Hello</h1><a name='world'></a>"#;
+        assert!(has_anchor(body, "hello"));
+        assert!(has_anchor(body, "world"));
+        assert!(!has_anchor(body, "hell"));
+    }
+
+    #[test]
+    fn can_find_encoded_and_github_anchors() {
+        let body = r#"<h2 id="café-au-lait">Café</h2><a id="user-content-installation"></a>"#;
+        assert!(has_anchor(body, "caf%C3%A9-au-lait"));
+        assert!(has_anchor(body, "installation"));
+        assert!(!has_anchor(body, "usage"));
+    }
+
+    #[test]
+    fn retry_delay_does_not_overflow() {
+        assert_eq!(retry_delay(1).as_secs(), 2);
+        assert_eq!(retry_delay(3).as_secs(), 8);
+        assert_eq!(retry_delay(10).as_secs(), 30);
+        assert_eq!(retry_delay(64).as_secs(), 30);
+        assert_eq!(retry_delay(100).as_secs(), 30);
+    }
 }
@@ -17,13 +17,16 @@ pub enum PageChangesNeeded {
     Taxonomies,
     /// Editing `date`, `order` or `weight`
     Sort,
+    /// Editing `series` or `series_part`, which changes the navigation of the other pages
+    /// of the series
+    Series,
     /// Editing anything causes a re-render of the page
     Render,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SectionChangesNeeded {
-    /// Editing `sort_by`
+    /// Editing `sort_by`, `sort_by_extra` or `reverse_sort`
     Sort,
     /// Editing `title`, `description`, `extra`, `template` or setting `render` to true
     Render,
@@ -33,6 +36,8 @@ pub enum SectionChangesNeeded {
     Delete,
     /// Changing `transparent`
     Transparent,
+    /// Editing `page_path`, which changes the path of all the pages of the section
+    PagePath,
 }
 
 /// Evaluates all the params in the front matter that changed so we can do the smallest
@@ -44,7 +49,10 @@ fn find_section_front_matter_changes(
 ) -> Vec<SectionChangesNeeded> {
     let mut changes_needed = vec![];
 
-    if current.sort_by != new.sort_by {
+    if current.sort_by != new.sort_by
+        || current.sort_by_extra != new.sort_by_extra
+        || current.reverse_sort != new.reverse_sort
+    {
         changes_needed.push(SectionChangesNeeded::Sort);
     }
 
@@ -52,6 +60,12 @@ fn find_section_front_matter_changes(
         changes_needed.push(SectionChangesNeeded::Transparent);
     }
 
+    if current.page_path != new.page_path {
+        changes_needed.push(SectionChangesNeeded::PagePath);
+        // All the pages need to be loaded again
+        return changes_needed;
+    }
+
     // We want to hide the section
     // TODO: what to do on redirect_path change?
     if current.render && !new.render {
@@ -92,6 +106,10 @@ fn find_page_front_matter_changes(
         changes_needed.push(PageChangesNeeded::Sort);
     }
 
+    if current.series != other.series || current.series_part != other.series_part {
+        changes_needed.push(PageChangesNeeded::Series);
+    }
+
     changes_needed.push(PageChangesNeeded::Render);
     changes_needed
 }
@@ -118,6 +136,7 @@ fn delete_element(site: &mut Site, path: &Path, is_section: bool) -> Result<()>
     }
 
     site.populate_sections();
+    site.library.populate_backlinks();
     site.populate_taxonomies()?;
     // Ensure we have our fn updated so it doesn't contain the permalink(s)/section/page deleted
     site.register_early_global_fns();
@@ -127,6 +146,32 @@ fn delete_element(site: &mut Site, path: &Path, is_section: bool) -> Result<()>
     site.build()
 }
 
+/// Re-renders the pages and sections that gained or lost a backlink after an edit.
+/// If the title of the edited content changed, all the content it links to is re-rendered
+/// since their backlinks show that title.
+fn render_backlinks_changes(
+    site: &Site,
+    old_links: &[String],
+    new_links: &[String],
+    title_changed: bool,
+) -> Result<()> {
+    let added_links = new_links.iter().filter(|l| !old_links.contains(l));
+    for link in old_links.iter().chain(added_links) {
+        if !title_changed && old_links.contains(link) && new_links.contains(link) {
+            continue;
+        }
+        if let Some(page) = site.library.pages().values().find(|p| p.file.relative == *link) {
+            site.render_page(page)?;
+        } else if let Some(section) =
+            site.library.sections().values().find(|s| s.file.relative == *link)
+        {
+            site.render_section(section, false)?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Handles a `_index.md` (a section) being edited in some ways
 fn handle_section_editing(site: &mut Site, path: &Path) -> Result<()> {
     let section = Section::from_file(path, &site.config)?;
@@ -135,6 +180,15 @@ fn handle_section_editing(site: &mut Site, path: &Path) -> Result<()> {
         // Updating a section
         Some(prev) => {
             site.populate_sections();
+            site.library.populate_backlinks();
+            site.register_tera_global_fns();
+            let section = site.library.get_section(&pathbuf).unwrap();
+            render_backlinks_changes(
+                site,
+                &prev.internal_links,
+                &section.internal_links,
+                section.meta.title != prev.meta.title,
+            )?;
 
             if site.library.get_section(&pathbuf).unwrap().meta == prev.meta {
                 // Front matter didn't change, only content did
@@ -162,6 +216,10 @@ fn handle_section_editing(site: &mut Site, path: &Path) -> Result<()> {
                     SectionChangesNeeded::Delete | SectionChangesNeeded::Transparent => {
                         site.build()?;
                     }
+                    SectionChangesNeeded::PagePath => {
+                        site.load()?;
+                        site.build()?;
+                    }
                 };
             }
             Ok(())
@@ -169,7 +227,14 @@ fn handle_section_editing(site: &mut Site, path: &Path) -> Result<()> {
         // New section, only render that one
         None => {
             site.populate_sections();
+            site.library.populate_backlinks();
             site.register_tera_global_fns();
+            render_backlinks_changes(
+                site,
+                &[],
+                &site.library.get_section(&pathbuf).unwrap().internal_links,
+                false,
+            )?;
             site.render_section(&site.library.get_section(&pathbuf).unwrap(), true)
         }
     }
@@ -186,12 +251,31 @@ macro_rules! render_parent_section {
 /// Handles a page being edited in some ways
 fn handle_page_editing(site: &mut Site, path: &Path) -> Result<()> {
     let page = Page::from_file(path, &site.config)?;
+    // A page that just became a draft or got a future date is handled as if it was deleted
+    if site.is_page_excluded(&page) {
+        return delete_element(site, path, false);
+    }
     let pathbuf = path.to_path_buf();
     match site.add_page(page, true)? {
         // Updating a page
         Some(prev) => {
             site.populate_sections();
+            site.library.populate_backlinks();
             site.populate_taxonomies()?;
+            site.register_tera_global_fns();
+            let page = site.library.get_page(&pathbuf).unwrap();
+            render_backlinks_changes(
+                site,
+                &prev.internal_links,
+                &page.internal_links,
+                page.meta.title != prev.meta.title,
+            )?;
+
+            // With `--drafts`, a page becoming a draft or getting published changes every
+            // listing it appears in
+            if page.meta.draft != prev.meta.draft {
+                return site.build();
+            }
 
             // Front matter didn't change, only content did
             if site.library.get_page(&pathbuf).unwrap().meta == prev.meta {
@@ -200,7 +284,6 @@ fn handle_page_editing(site: &mut Site, path: &Path) -> Result<()> {
                 if site.library.get_page(&pathbuf).unwrap().summary.is_some() {
                     render_parent_section!(site, path);
                 }
-                site.register_tera_global_fns();
                 return site.render_page(&site.library.get_page(&pathbuf).unwrap());
             }
 
@@ -216,10 +299,20 @@ fn handle_page_editing(site: &mut Site, path: &Path) -> Result<()> {
                     PageChangesNeeded::Taxonomies => {
                         site.populate_taxonomies()?;
                         site.render_taxonomies()?;
+                        // The related pages of any page could have changed
+                        if site.config.related_pages.is_enabled() {
+                            site.render_sections()?;
+                            site.render_orphan_pages()?;
+                        }
                     }
                     PageChangesNeeded::Sort => {
                         site.render_index()?;
                     }
+                    PageChangesNeeded::Series => {
+                        // The pages of a series can be anywhere in the site
+                        site.render_sections()?;
+                        site.render_orphan_pages()?;
+                    }
                     PageChangesNeeded::Render => {
                         render_parent_section!(site, path);
                         site.render_page(&site.library.get_page(&path.to_path_buf()).unwrap())?;
@@ -231,6 +324,7 @@ fn handle_page_editing(site: &mut Site, path: &Path) -> Result<()> {
         // It's a new page!
         None => {
             site.populate_sections();
+            site.library.populate_backlinks();
             site.populate_taxonomies()?;
             site.register_early_global_fns();
             site.register_tera_global_fns();
@@ -272,7 +366,7 @@ pub fn after_content_rename(site: &mut Site, old: &Path, new: &Path) -> Result<(
     }
 
     // Renaming a file to _index.md, let the section editing do something and hope for the best
-    if new_path.file_name().unwrap() == "_index.md" {
+    if is_section_file(&new_path) {
         // We aren't entirely sure where the original thing was so just try to delete whatever was
         // at the old path
         site.library.remove_page(&old.to_path_buf());
@@ -291,9 +385,14 @@ pub fn after_content_rename(site: &mut Site, old: &Path, new: &Path) -> Result<(
     return handle_page_editing(site, &new_path);
 }
 
+/// Whether the file is a section, either `_index.md` or a translation like `_index.fr.md`
+fn is_section_file(path: &Path) -> bool {
+    path.file_name().unwrap().to_string_lossy().starts_with("_index.")
+}
+
 /// What happens when a section or a page is created/edited
 pub fn after_content_change(site: &mut Site, path: &Path) -> Result<()> {
-    let is_section = path.file_name().unwrap() == "_index.md";
+    let is_section = is_section_file(path);
     let is_md = path.extension().unwrap() == "md";
     let index = path.parent().unwrap().join("index.md");
 
@@ -309,13 +408,23 @@ pub fn after_content_change(site: &mut Site, path: &Path) -> Result<()> {
     //       1. Nothing? Return Ok
     //       2. Something? Update the page
     if is_md {
+        // The content of taxonomy terms is only known once the taxonomies are populated
+        if site.is_term_content_file(path) {
+            site.load()?;
+            return site.build();
+        }
+
+        // Translations of an `index.md` live in the same folder, eg `index.fr.md`
+        let is_index_translation =
+            path != index && path.file_name().unwrap().to_string_lossy().starts_with("index.");
+
         // only delete if it was able to be added in the first place
-        if !index.exists() && !path.exists() {
+        if (!index.exists() || is_index_translation) && !path.exists() {
             delete_element(site, path, is_section)?;
         }
 
         // Added another .md in a assets directory
-        if index.exists() && path.exists() && path != index {
+        if index.exists() && path.exists() && path != index && !is_index_translation {
             bail!(
                 "Change on {:?} detected but there is already an `index.md` in the same folder",
                 path.display()
@@ -345,6 +454,7 @@ pub fn after_template_change(site: &mut Site, path: &Path) -> Result<()> {
     match filename {
         "sitemap.xml" => site.render_sitemap(),
         "rss.xml" => site.render_rss_feed(site.library.pages_values(), None),
+        "atom.xml" => site.render_atom_feed(site.library.pages_values(), None),
         "robots.txt" => site.render_robots(),
         "single.html" | "list.html" => site.render_taxonomies(),
         "page.html" => {
@@ -364,6 +474,7 @@ pub fn after_template_change(site: &mut Site, path: &Path) -> Result<()> {
                 site.render_markdown()?;
             }
             site.populate_sections();
+            site.library.populate_backlinks();
             site.populate_taxonomies()?;
             site.render_sections()?;
             site.render_orphan_pages()?;
@@ -403,6 +514,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_find_series_changes_in_page_frontmatter() {
+        let new =
+            PageFrontMatter { series: Some("A series".to_string()), ..PageFrontMatter::default() };
+        let changes = find_page_front_matter_changes(&PageFrontMatter::default(), &new);
+        assert_eq!(changes, vec![PageChangesNeeded::Series, PageChangesNeeded::Render]);
+    }
+
     #[test]
     fn can_find_sort_changes_in_section_frontmatter() {
         let new = SectionFrontMatter { sort_by: SortBy::Date, ..SectionFrontMatter::default() };
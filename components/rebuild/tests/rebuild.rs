@@ -100,6 +100,61 @@ Some content"#
     assert!(file_contains!(site_path, "public/rebuild/first/index.html", "<p>Some content</p>"));
 }
 
+#[test]
+fn can_rebuild_after_page_becomes_draft() {
+    let tmp_dir = tempdir().expect("create temp dir");
+    let (site_path, mut site) = load_and_build_site!(tmp_dir);
+    let file_path = edit_file!(
+        site_path,
+        "content/rebuild/first.md",
+        br#"
++++
+title = "first"
+weight = 1
+date = 2017-01-01
+draft = true
++++
+
+Some content"#
+    );
+
+    let res = after_content_change(&mut site, &file_path);
+    assert!(res.is_ok());
+    assert!(!site.library.contains_page(&file_path));
+}
+
+#[test]
+fn can_rebuild_with_drafts_after_publishing_a_draft() {
+    let tmp_dir = tempdir().expect("create temp dir");
+    let path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().join("test_site");
+    let mut options = dir::CopyOptions::new();
+    options.copy_inside = true;
+    dir::copy(&path, &tmp_dir, &options).unwrap();
+    let site_path = tmp_dir.path().join("test_site");
+    let mut site = Site::new(&site_path, "config.toml").unwrap();
+    site.include_drafts();
+    site.load().unwrap();
+    site.set_output_path(site_path.join("public"));
+    site.build().unwrap();
+    assert!(!file_contains!(site_path, "public/sitemap.xml", "posts/draft/"));
+
+    let file_path = edit_file!(
+        site_path,
+        "content/posts/draft.md",
+        br#"
++++
+title = "A draft"
+date = 2016-03-01
++++
+
+Not a draft anymore"#
+    );
+
+    let res = after_content_change(&mut site, &file_path);
+    assert!(res.is_ok());
+    assert!(file_contains!(site_path, "public/sitemap.xml", "posts/draft/"));
+}
+
 #[test]
 fn can_rebuild_after_title_change_page_global_func_usage() {
     let tmp_dir = tempdir().expect("create temp dir");
@@ -234,3 +289,88 @@ fn can_rebuild_after_renaming_non_md_asset_in_colocated_folder() {
     let res = after_content_rename(&mut site, &old_path, &new_path);
     assert!(res.is_ok());
 }
+
+#[test]
+fn can_rebuild_after_change_to_term_content() {
+    let tmp_dir = tempdir().expect("create temp dir");
+    let (site_path, mut site) = load_and_build_site!(tmp_dir);
+    let file_path = edit_file!(
+        site_path,
+        "content/categories/a-category/_index.md",
+        br#"
++++
+title = "Another title"
++++
+
+Some content"#
+    );
+
+    let res = after_content_change(&mut site, &file_path);
+    assert!(res.is_ok());
+    assert!(!site.library.contains_section(&file_path));
+    assert!(file_contains!(
+        site_path,
+        "public/categories/a-category/index.html",
+        "Title: Another title"
+    ));
+    assert!(file_contains!(
+        site_path,
+        "public/categories/a-category/index.html",
+        "<p>Some content</p>"
+    ));
+}
+
+#[test]
+fn can_rebuild_linked_page_after_adding_an_internal_link() {
+    let tmp_dir = tempdir().expect("create temp dir");
+    let (site_path, mut site) = load_and_build_site!(tmp_dir);
+    let file_path = edit_file!(
+        site_path,
+        "content/posts/fixed-slug.md",
+        br#"
++++
+title = "Fixed slug"
+description = ""
+slug = "something-else"
+date = 2017-01-01
+aliases = ["/an-old-url/old-page", "/an-old-url/an-old-alias.html"]
++++
+
+A simple page linking to [Python](./posts/python.md)"#
+    );
+
+    let res = after_content_change(&mut site, &file_path);
+    assert!(res.is_ok());
+    assert!(file_contains!(
+        site_path,
+        "public/posts/python/index.html",
+        "Referenced by: Fixed slug (https://replace-this-with-your-url.com/posts/something-else/)"
+    ));
+}
+
+#[test]
+fn can_rebuild_linked_page_after_changing_the_title_of_a_backlink() {
+    let tmp_dir = tempdir().expect("create temp dir");
+    let (site_path, mut site) = load_and_build_site!(tmp_dir);
+    let file_path = edit_file!(
+        site_path,
+        "content/posts/simple.md",
+        br#"
++++
+title = "A renamed article"
+description = ""
+date = 2017-04-01
+updated = 2018-02-01
++++
+
+A simple page, see also [the Python post](./posts/python.md)."#
+    );
+
+    let res = after_content_change(&mut site, &file_path);
+    assert!(res.is_ok());
+    assert!(file_contains!(
+        site_path,
+        "public/posts/python/index.html",
+        "Referenced by: A renamed article (https://replace-this-with-your-url.com/posts/simple/)"
+    ));
+}
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
+use std::sync::Mutex;
 
 use config::Config;
 use front_matter::InsertAnchor;
+use imageproc::Processor;
 use tera::{Context, Tera};
 
 /// All the information from the zola site that is needed to render HTML from markdown
@@ -13,6 +15,11 @@ pub struct RenderContext<'a> {
     pub current_page_permalink: &'a str,
     pub permalinks: &'a HashMap<String, String>,
     pub insert_anchor: InsertAnchor,
+    /// Only set when the markdown images need to be processed, see `markdown_images` in the config
+    pub imageproc: Option<&'a Mutex<Processor>>,
+    /// The directory of the current page, relative to the `content` directory.
+    /// Used to find the colocated images to process
+    pub current_page_dir: String,
 }
 
 impl<'a> RenderContext<'a> {
@@ -32,6 +39,8 @@ impl<'a> RenderContext<'a> {
             permalinks,
             insert_anchor,
             config,
+            imageproc: None,
+            current_page_dir: String::new(),
         }
     }
 }
@@ -16,7 +16,7 @@ extern crate lazy_static;
 extern crate errors;
 extern crate config;
 extern crate front_matter;
-extern crate link_checker;
+extern crate imageproc;
 extern crate utils;
 
 #[cfg(test)]
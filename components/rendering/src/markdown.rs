@@ -9,8 +9,10 @@ use syntect::html::{
 };
 
 use config::highlighting::{get_highlighter, SYNTAX_SET, THEME_SET};
+use config::ImageFormat;
 use errors::Result;
-use link_checker::check_url;
+use imageproc;
+use tera::escape_html;
 use utils::site::resolve_internal_link;
 
 use context::RenderContext;
@@ -23,6 +25,10 @@ pub struct Rendered {
     pub body: String,
     pub summary_len: Option<usize>,
     pub toc: Vec<Header>,
+    /// The external links found in the content, only filled if `check_external_links` is set
+    pub external_links: Vec<String>,
+    /// The content files linked with `./` links, relative to the `content` directory and without anchor
+    pub internal_links: Vec<String>,
 }
 
 // We might have cases where the slug is already present in our list of anchor
@@ -49,6 +55,75 @@ fn is_colocated_asset_link(link: &str) -> bool {
         && !link.starts_with("mailto:")
 }
 
+/// A markdown image that went through `imageproc`, rendered as raw HTML once we have its alt text
+#[derive(Debug)]
+struct ProcessedImage {
+    url: String,
+    width: u32,
+    height: u32,
+    title: String,
+    alt: String,
+}
+
+impl ProcessedImage {
+    fn to_html(&self, lazy_loading: bool) -> String {
+        let mut html = format!("<img src=\"{}\" alt=\"{}\"", self.url, escape_html(&self.alt));
+        if !self.title.is_empty() {
+            html.push_str(&format!(" title=\"{}\"", escape_html(&self.title)));
+        }
+        html.push_str(&format!(" width=\"{}\" height=\"{}\"", self.width, self.height));
+        if lazy_loading {
+            html.push_str(" loading=\"lazy\"");
+        }
+        html.push_str(" />");
+        html
+    }
+}
+
+/// Enqueues the resizing of a colocated image in `imageproc` if needed and returns its URL
+/// and its final dimensions.
+/// Returns `None` if the images are not processed or if that image can't be found.
+fn process_image(src: &str, context: &RenderContext) -> Result<Option<(String, u32, u32)>> {
+    let settings = &context.config.markdown_images;
+    let imageproc = match context.imageproc {
+        Some(i) if settings.process => i,
+        _ => return Ok(None),
+    };
+    if !is_colocated_asset_link(src) || !imageproc::file_is_img(src) {
+        return Ok(None);
+    }
+
+    let source = if context.current_page_dir.is_empty() {
+        src.to_string()
+    } else {
+        format!("{}/{}", context.current_page_dir, src)
+    };
+    // Content is rendered in parallel so the processor is only locked to insert the operation
+    let source_path = imageproc.lock().unwrap().source_path(&source);
+    if !source_path.exists() {
+        return Ok(None);
+    }
+
+    let (width, height) = imageproc::read_image_dimensions(&source_path)?;
+    let too_wide = settings.max_width > 0 && width > settings.max_width;
+    // Images are only re-encoded if they are too large or if a specific format is wanted
+    if !too_wide && settings.format == ImageFormat::Auto {
+        return Ok(Some((format!("{}{}", context.current_page_permalink, src), width, height)));
+    }
+
+    let op = imageproc::ResizeOp::FitWidth(if too_wide { settings.max_width } else { width });
+    let (width, height) = op.output_dimensions(width, height);
+    let quality = settings.quality;
+    let format = match settings.format {
+        ImageFormat::Auto => imageproc::Format::auto(&source_path, quality)?,
+        ImageFormat::Jpg => imageproc::Format::Jpeg(quality),
+        ImageFormat::Png => imageproc::Format::Png,
+        ImageFormat::Webp => imageproc::Format::WebP(quality),
+    };
+    let url = imageproc.lock().unwrap().insert(imageproc::ImageOp::new(source, op, format));
+    Ok(Some((url, width, height)))
+}
+
 pub fn markdown_to_html(content: &str, context: &RenderContext) -> Result<Rendered> {
     // the rendered html
     let mut html = String::with_capacity(content.len());
@@ -63,6 +138,10 @@ pub fn markdown_to_html(content: &str, context: &RenderContext) -> Result<Render
     // specific characters like `!` in them. We only want to insert the anchor the first time
     let mut header_created = false;
     let mut anchors: Vec<String> = vec![];
+    let mut external_links = vec![];
+    let mut internal_links = vec![];
+    // Set while going through the alt text of an image processed by `imageproc`
+    let mut processed_image: Option<ProcessedImage> = None;
 
     let mut headers = vec![];
     // Defaults to a 0 level so not a real header
@@ -76,6 +155,21 @@ pub fn markdown_to_html(content: &str, context: &RenderContext) -> Result<Render
 
     {
         let parser = Parser::new_ext(content, opts).map(|event| {
+            // The alt text of processed images is collected and the image is output
+            // as HTML at the end, anything else in the alt text is dropped
+            if processed_image.is_some() {
+                match event {
+                    Event::End(Tag::Image(_, _)) => {
+                        let image = processed_image.take().unwrap();
+                        let lazy_loading = context.config.markdown_images.lazy_loading;
+                        return Event::Html(Owned(image.to_html(lazy_loading)));
+                    }
+                    Event::Text(ref text) => processed_image.as_mut().unwrap().alt.push_str(text),
+                    _ => (),
+                }
+                return Event::Html(Borrowed(""));
+            }
+
             match event {
                 Event::Text(text) => {
                     // Header first
@@ -132,6 +226,24 @@ pub fn markdown_to_html(content: &str, context: &RenderContext) -> Result<Render
                     Event::Html(Borrowed("</pre>"))
                 }
                 Event::Start(Tag::Image(src, title)) => {
+                    if !in_header {
+                        match process_image(&src, context) {
+                            Ok(Some((url, width, height))) => {
+                                let title = title.to_string();
+                                let alt = String::new();
+                                processed_image =
+                                    Some(ProcessedImage { url, width, height, title, alt });
+                                return Event::Html(Borrowed(""));
+                            }
+                            Ok(None) => (),
+                            Err(e) => {
+                                error = Some(
+                                    e.chain_err(|| format!("Failed to process image {}", src)),
+                                );
+                            }
+                        }
+                    }
+
                     if is_colocated_asset_link(&src) {
                         return Event::Start(Tag::Image(
                             Owned(format!("{}{}", context.current_page_permalink, src)),
@@ -150,7 +262,12 @@ pub fn markdown_to_html(content: &str, context: &RenderContext) -> Result<Render
                     //   we need to append to a string
                     let fixed_link = if link.starts_with("./") {
                         match resolve_internal_link(&link, context.permalinks) {
-                            Ok(url) => url,
+                            Ok(url) => {
+                                let file_path = link.replacen("./", "", 1);
+                                internal_links
+                                    .push(file_path.split('#').next().unwrap().to_string());
+                                url
+                            }
                             Err(_) => {
                                 error = Some(format!("Relative link {} not found.", link).into());
                                 return Event::Html(Borrowed(""));
@@ -162,15 +279,9 @@ pub fn markdown_to_html(content: &str, context: &RenderContext) -> Result<Render
                         && !link.starts_with('#')
                         && !link.starts_with("mailto:")
                     {
-                        let res = check_url(&link);
-                        if res.is_valid() {
-                            link.to_string()
-                        } else {
-                            error = Some(
-                                format!("Link {} is not valid: {}", link, res.message()).into(),
-                            );
-                            String::new()
-                        }
+                        // They are all checked at once after rendering
+                        external_links.push(link.to_string());
+                        link.to_string()
                     } else {
                         link.to_string()
                     };
@@ -246,6 +357,8 @@ pub fn markdown_to_html(content: &str, context: &RenderContext) -> Result<Render
             summary_len: if has_summary { html.find(CONTINUE_READING) } else { None },
             body: html,
             toc: make_table_of_contents(&headers),
+            external_links,
+            internal_links,
         })
     }
 }
@@ -1,15 +1,19 @@
 extern crate config;
 extern crate front_matter;
+extern crate imageproc;
 extern crate rendering;
 extern crate templates;
 extern crate tera;
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use tera::Tera;
 
 use config::Config;
 use front_matter::InsertAnchor;
+use imageproc::Processor;
 use rendering::{render_content, RenderContext};
 use templates::ZOLA_TERA;
 
@@ -309,6 +313,22 @@ fn can_make_valid_relative_link() {
     );
 }
 
+#[test]
+fn can_collect_internal_links() {
+    let mut permalinks = HashMap::new();
+    permalinks.insert("pages/about.md".to_string(), "https://vincent.is/about".to_string());
+    let tera_ctx = Tera::default();
+    let config = Config::default();
+    let context = RenderContext::new(&tera_ctx, &config, "", &permalinks, InsertAnchor::None);
+    let res = render_content(
+        r#"[rel link](./pages/about.md), [anchor](./pages/about.md#hello), [abs link](https://vincent.is/about)"#,
+        &context,
+    )
+    .unwrap();
+
+    assert_eq!(res.internal_links, vec!["pages/about.md", "pages/about.md"]);
+}
+
 #[test]
 fn can_make_relative_links_with_anchors() {
     let mut permalinks = HashMap::new();
@@ -570,6 +590,61 @@ fn can_make_permalinks_with_colocated_assets_for_image() {
     );
 }
 
+fn test_site_imageproc() -> Mutex<Processor> {
+    let content_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../test_site/content");
+    Mutex::new(Processor::new(content_path, Path::new("static"), "https://vincent.is"))
+}
+
+#[test]
+fn can_add_dimensions_to_processed_markdown_images() {
+    let permalinks_ctx = HashMap::new();
+    let mut config = Config::default();
+    config.markdown_images.process = true;
+    let imageproc = test_site_imageproc();
+    let mut context = RenderContext::new(
+        &ZOLA_TERA,
+        &config,
+        "https://vincent.is/posts/with-assets/",
+        &permalinks_ctx,
+        InsertAnchor::None,
+    );
+    context.imageproc = Some(&imageproc);
+    context.current_page_dir = "posts/with-assets".to_string();
+
+    let res =
+        render_content("![alt *text*](zola.png \"Zola\") ![missing](nope.png)", &context).unwrap();
+    assert_eq!(
+        res.body,
+        "<p><img src=\"https://vincent.is/posts/with-assets/zola.png\" alt=\"alt text\" title=\"Zola\" width=\"256\" height=\"256\" loading=\"lazy\" /> <img src=\"https://vincent.is/posts/with-assets/nope.png\" alt=\"missing\" /></p>\n"
+    );
+    // The image is small enough and keeps its format so it doesn't need processing
+    assert_eq!(imageproc.lock().unwrap().num_img_ops(), 0);
+}
+
+#[test]
+fn can_resize_large_markdown_images() {
+    let permalinks_ctx = HashMap::new();
+    let mut config = Config::default();
+    config.markdown_images.process = true;
+    config.markdown_images.max_width = 100;
+    config.markdown_images.lazy_loading = false;
+    let imageproc = test_site_imageproc();
+    let mut context = RenderContext::new(
+        &ZOLA_TERA,
+        &config,
+        "https://vincent.is/posts/with-assets/",
+        &permalinks_ctx,
+        InsertAnchor::None,
+    );
+    context.imageproc = Some(&imageproc);
+    context.current_page_dir = "posts/with-assets".to_string();
+
+    let res = render_content("![alt](zola.png)", &context).unwrap();
+    assert!(res.body.starts_with("<p><img src=\"https://vincent.is/processed_images/"));
+    assert!(res.body.ends_with(".png\" alt=\"alt\" width=\"100\" height=\"100\" /></p>\n"));
+    assert_eq!(imageproc.lock().unwrap().num_img_ops(), 1);
+}
+
 #[test]
 fn markdown_doesnt_wrap_html_in_paragraph() {
     let permalinks_ctx = HashMap::new();
@@ -603,7 +678,7 @@ Some text
 }
 
 #[test]
-fn can_validate_valid_external_links() {
+fn can_collect_external_links() {
     let permalinks_ctx = HashMap::new();
     let mut config = Config::default();
     config.check_external_links = true;
@@ -614,15 +689,20 @@ fn can_validate_valid_external_links() {
         &permalinks_ctx,
         InsertAnchor::None,
     );
-    let res = render_content("[a link](http://google.com)", &context).unwrap();
-    assert_eq!(res.body, "<p><a href=\"http://google.com\">a link</a></p>\n");
+    let res = render_content("[a link](http://google.com) [another](http://google.comy)", &context)
+        .unwrap();
+    assert_eq!(
+        res.body,
+        "<p><a href=\"http://google.com\">a link</a> <a href=\"http://google.comy\">another</a></p>\n"
+    );
+    // Links are only checked once all the content is rendered
+    assert_eq!(res.external_links, vec!["http://google.com", "http://google.comy"]);
 }
 
 #[test]
-fn can_show_error_message_for_invalid_external_links() {
+fn doesnt_collect_external_links_if_not_checking_them() {
     let permalinks_ctx = HashMap::new();
-    let mut config = Config::default();
-    config.check_external_links = true;
+    let config = Config::default();
     let context = RenderContext::new(
         &ZOLA_TERA,
         &config,
@@ -630,10 +710,8 @@ fn can_show_error_message_for_invalid_external_links() {
         &permalinks_ctx,
         InsertAnchor::None,
     );
-    let res = render_content("[a link](http://google.comy)", &context);
-    assert!(res.is_err());
-    let err = res.unwrap_err();
-    assert!(err.description().contains("Link http://google.comy is not valid"));
+    let res = render_content("[a link](http://google.com)", &context).unwrap();
+    assert!(res.external_links.is_empty());
 }
 
 #[test]
@@ -650,6 +728,7 @@ fn doesnt_try_to_validate_email_links_mailto() {
     );
     let res = render_content("Email: [foo@bar.baz](mailto:foo@bar.baz)", &context).unwrap();
     assert_eq!(res.body, "<p>Email: <a href=\"mailto:foo@bar.baz\">foo@bar.baz</a></p>\n");
+    assert!(res.external_links.is_empty());
 }
 
 #[test]
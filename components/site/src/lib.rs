@@ -12,6 +12,7 @@ extern crate config;
 extern crate front_matter;
 extern crate imageproc;
 extern crate library;
+extern crate link_checker;
 extern crate search;
 extern crate templates;
 extern crate utils;
@@ -34,10 +35,15 @@ use errors::{Result, ResultExt};
 use front_matter::InsertAnchor;
 use library::{
     find_taxonomies, sort_actual_pages_by_date, Library, Page, Paginator, Section, Taxonomy,
+    TermContent,
 };
 use templates::{global_fns, render_redirect_template, ZOLA_TERA};
-use utils::fs::{copy_directory, create_directory, create_file, ensure_directory_exists};
+use utils::fs::{
+    copy_directory, create_directory, create_file, ensure_directory_exists, get_file_last_modified,
+    get_last_modified_dates,
+};
 use utils::net::get_available_port;
+use utils::site::to_rfc3339;
 use utils::templates::{render_template, rewrite_theme_paths};
 
 /// The sitemap only needs links and potentially date so we trim down
@@ -54,6 +60,30 @@ impl SitemapEntry {
     }
 }
 
+/// An external link that didn't pass the link checker
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BrokenLink {
+    /// The content file containing the link, relative to the `content` directory
+    pub file: String,
+    pub link: String,
+    /// The HTTP status code or the error we got while checking the link
+    pub message: String,
+}
+
+/// How many pagers a paginated section or taxonomy term will have, there is always at least one
+fn num_pagers(num_pages: usize, paginate_by: usize) -> usize {
+    ((num_pages + paginate_by - 1) / paginate_by).max(1)
+}
+
+/// An output file that more than one thing would be rendered to
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PathCollision {
+    /// The file path, relative to the output directory
+    pub path: String,
+    /// Descriptions of what would be rendered there, eg "page `posts/hello.md`"
+    pub sources: Vec<String>,
+}
+
 #[derive(Debug)]
 pub struct Site {
     /// The base path of the zola site
@@ -68,11 +98,20 @@ pub struct Site {
     content_path: PathBuf,
     pub static_path: PathBuf,
     pub taxonomies: Vec<Taxonomy>,
+    /// The `content/{taxonomy}/{slug}/_index.md` files giving content to taxonomy terms,
+    /// keyed by `{taxonomy}/{slug}`
+    pub term_sections: HashMap<String, Section>,
     /// A map of all .md files (section and pages) and their permalink
     /// We need that if there are relative links in the content that need to be resolved
     pub permalinks: HashMap<String, String>,
     /// Contains all pages and sections of the site
     pub library: Library,
+    /// The broken external links found by `check_external_links`, sorted by file
+    pub broken_links: Vec<BrokenLink>,
+    /// Whether to load draft pages, they are left out of the site by default
+    pub include_drafts: bool,
+    /// The output paths used more than once found by `check_path_collisions`
+    pub path_collisions: Vec<PathCollision>,
 }
 
 impl Site {
@@ -139,9 +178,13 @@ impl Site {
             content_path,
             static_path,
             taxonomies: Vec::new(),
+            term_sections: HashMap::new(),
             permalinks: HashMap::new(),
             // We will allocate it properly later on
             library: Library::new(0, 0),
+            broken_links: Vec::new(),
+            include_drafts: false,
+            path_collisions: Vec::new(),
         };
 
         Ok(site)
@@ -170,6 +213,41 @@ impl Site {
         self.config.base_url = base_url;
     }
 
+    /// Load the draft pages as well instead of leaving them out of the site
+    pub fn include_drafts(&mut self) {
+        self.include_drafts = true;
+    }
+
+    /// Load the pages dated in the future as well, whatever `build_future` is set to
+    pub fn include_future_pages(&mut self) {
+        self.config.build_future = true;
+    }
+
+    /// Whether that page should be left out of the site entirely: drafts unless they
+    /// are included and future pages if `build_future` is disabled
+    pub fn is_page_excluded(&self, page: &Page) -> bool {
+        (page.is_draft() && !self.include_drafts)
+            || (page.is_in_future(self.config.build_datetime()) && !self.config.build_future)
+    }
+
+    /// Whether that file is a `content/{taxonomy}/{slug}/_index.md` file giving content to
+    /// a taxonomy term rather than a section
+    pub fn is_term_content_file(&self, path: &Path) -> bool {
+        let components: Vec<_> = match path.strip_prefix(&self.content_path) {
+            Ok(p) => p.components().map(|c| c.as_os_str().to_string_lossy()).collect(),
+            Err(_) => return false,
+        };
+
+        // Terms of hierarchical taxonomies can be nested, eg `content/categories/programming/rust/`
+        components.len() >= 3
+            && components[components.len() - 1] == "_index.md"
+            && self
+                .config
+                .taxonomies
+                .iter()
+                .any(|t| t.name == components[0] && (components.len() == 3 || t.hierarchical))
+    }
+
     pub fn set_output_path<P: AsRef<Path>>(&mut self, path: P) {
         self.output_path = path.as_ref().to_path_buf();
     }
@@ -184,7 +262,13 @@ impl Site {
             .unwrap()
             .filter_map(|e| e.ok())
             .filter(|e| !e.as_path().file_name().unwrap().to_str().unwrap().starts_with('.'))
-            .partition(|entry| entry.as_path().file_name().unwrap() == "_index.md");
+            .partition(|entry| {
+                entry.as_path().file_name().unwrap().to_str().unwrap().starts_with("_index.")
+            });
+
+        let (term_entries, section_entries): (Vec<_>, Vec<_>) = section_entries
+            .into_iter()
+            .partition(|entry| self.is_term_content_file(entry.as_path()));
 
         self.library = Library::new(page_entries.len(), section_entries.len());
 
@@ -212,10 +296,25 @@ impl Site {
                 .collect::<Vec<_>>()
         };
 
+        // Asking git for the date of every file at once is much faster than once per file
+        let last_modified_dates = if self.config.git_last_modified {
+            get_last_modified_dates(&self.content_path)
+        } else {
+            HashMap::new()
+        };
+
+        self.term_sections = HashMap::new();
+        for entry in term_entries {
+            let section = Section::from_file(entry.as_path(), &self.config)?;
+            self.permalinks.insert(section.file.relative.clone(), section.permalink.clone());
+            self.term_sections.insert(section.file.components.join("/"), section);
+        }
+
         // Kinda duplicated code for add_section/add_page but necessary to do it that
         // way because of the borrow checker
         for section in sections {
-            let s = section?;
+            let mut s = section?;
+            s.last_modified = last_modified_dates.get(&s.file.path).cloned();
             self.add_section(s, false)?;
         }
 
@@ -235,18 +334,47 @@ impl Site {
         if !self.library.contains_section(&index_path) {
             let mut index_section = Section::default();
             index_section.permalink = self.config.make_permalink("");
+            index_section.lang = self.config.default_language.clone();
             index_section.file.path = self.content_path.join("_index.md");
             index_section.file.parent = self.content_path.clone();
+            index_section.file.name = "_index".to_string();
+            index_section.file.filename = "_index.md".to_string();
+            index_section.file.canonical = self.content_path.join("_index");
             index_section.file.relative = "_index.md".to_string();
             self.library.insert_section(index_section);
         }
 
+        // Same thing for the index section of every other language
+        for language in &self.config.languages {
+            let filename = format!("_index.{}.md", language.code);
+            let index_path = self.content_path.join(&filename);
+            if self.library.contains_section(&index_path) {
+                continue;
+            }
+            let mut index_section = Section::default();
+            index_section.permalink = self.config.make_permalink(&language.code);
+            index_section.path = format!("{}/", language.code);
+            index_section.components = vec![language.code.clone()];
+            index_section.lang = language.code.clone();
+            index_section.file.path = index_path;
+            index_section.file.parent = self.content_path.clone();
+            index_section.file.name = "_index".to_string();
+            index_section.file.filename = filename.clone();
+            index_section.file.canonical = self.content_path.join("_index");
+            index_section.file.relative = filename;
+            self.library.insert_section(index_section);
+        }
+
         let mut pages_insert_anchors = HashMap::new();
         for page in pages {
-            let p = page?;
+            let mut p = page?;
+            if self.is_page_excluded(&p) {
+                continue;
+            }
+            p.last_modified = last_modified_dates.get(&p.file.path).cloned();
             pages_insert_anchors.insert(
                 p.file.path.clone(),
-                self.find_parent_section_insert_anchor(&p.file.parent.clone()),
+                self.find_parent_section_insert_anchor(&p.file.parent, &p.lang),
             );
             self.add_page(p, false)?;
         }
@@ -254,12 +382,196 @@ impl Site {
         self.register_early_global_fns();
         self.populate_sections();
         self.render_markdown()?;
+        // The internal links are only known once the markdown is rendered
+        self.library.populate_backlinks();
         self.populate_taxonomies()?;
         self.register_tera_global_fns();
+        self.check_path_collisions()?;
+
+        if self.config.check_external_links {
+            self.check_external_links()?;
+        }
+
+        Ok(())
+    }
+
+    /// Finds all the files that would be written more than once in the output directory:
+    /// pages, sections, paginated pages, taxonomies and aliases.
+    /// Errors with a report of all the collisions unless `path_collisions_as_warnings` is set,
+    /// in which case they are only available in `path_collisions`
+    pub fn check_path_collisions(&mut self) -> Result<()> {
+        // (path, what is rendered there)
+        let mut rendered = vec![];
+
+        for page in self.library.pages_values() {
+            let source = format!("page `{}`", page.file.relative);
+            for alias in &page.meta.aliases {
+                rendered.push((alias.clone(), format!("alias `{}` of {}", alias, source)));
+            }
+            rendered.push((page.path.clone(), source));
+        }
+
+        for section in self.library.sections_values() {
+            if !section.meta.render {
+                continue;
+            }
+            let source = format!("section `{}`", section.file.relative);
+            if section.meta.redirect_to.is_none() && section.meta.is_paginated() {
+                let num_pages = section
+                    .pages
+                    .iter()
+                    .filter(|k| !self.library.get_page_by_key(**k).is_draft())
+                    .count();
+                let num_pagers = num_pagers(num_pages, section.meta.paginate_by.unwrap());
+                for index in 1..=num_pagers {
+                    rendered.push((
+                        format!("{}/{}/{}", section.path, section.meta.paginate_path, index),
+                        format!("page {} of {}", index, source),
+                    ));
+                }
+            }
+            rendered.push((section.path.clone(), source));
+        }
+
+        for taxonomy in &self.taxonomies {
+            if taxonomy.items.is_empty() {
+                continue;
+            }
+            let name = &taxonomy.kind.name;
+            rendered.push((name.clone(), format!("taxonomy `{}`", name)));
+            for item in &taxonomy.items {
+                let path = format!("{}/{}", name, item.slug);
+                let source = format!("term `{}` of taxonomy `{}`", item.path, name);
+                if taxonomy.kind.is_paginated() {
+                    let paginate_path =
+                        taxonomy.kind.paginate_path.clone().unwrap_or_else(|| "pages".to_string());
+                    let num_pagers =
+                        num_pagers(item.pages.len(), taxonomy.kind.paginate_by.unwrap());
+                    for index in 1..=num_pagers {
+                        rendered.push((
+                            format!("{}/{}/{}", path, paginate_path, index),
+                            format!("page {} of {}", index, source),
+                        ));
+                    }
+                }
+                rendered.push((path, source));
+            }
+        }
+
+        let mut outputs: HashMap<String, Vec<String>> = HashMap::new();
+        for (path, source) in rendered {
+            let mut components = path.split('/').filter(|c| !c.is_empty()).collect::<Vec<_>>();
+            // Same as `render_aliases`, anything not ending with an HTML file is a directory
+            if !components.last().map_or(false, |c| c.ends_with(".html")) {
+                components.push("index.html");
+            }
+            outputs.entry(components.join("/")).or_default().push(source);
+        }
+
+        let mut collisions: Vec<PathCollision> = outputs
+            .into_iter()
+            .filter(|(_, sources)| sources.len() > 1)
+            .map(|(path, mut sources)| {
+                sources.sort();
+                PathCollision { path, sources }
+            })
+            .collect();
+        collisions.sort();
+        self.path_collisions = collisions;
+
+        if !self.path_collisions.is_empty() && !self.config.path_collisions_as_warnings {
+            bail!(self.path_collisions_report());
+        }
+
+        Ok(())
+    }
+
+    /// A human readable list of all the path collisions found
+    pub fn path_collisions_report(&self) -> String {
+        let mut report = format!("Found {} output path collision(s):", self.path_collisions.len());
+        for collision in &self.path_collisions {
+            report.push_str(&format!("\n{} would be rendered by:", collision.path));
+            for source in &collision.sources {
+                report.push_str(&format!("\n  - {}", source));
+            }
+        }
+        report
+    }
+
+    /// Checks all the external links found while rendering the markdown in parallel.
+    /// Errors with a report of all the broken links unless `failures_as_warnings` is set,
+    /// in which case they are only available in `broken_links`
+    pub fn check_external_links(&mut self) -> Result<()> {
+        let link_checker_config = &self.config.link_checker;
+        let mut links = vec![];
+        for page in self.library.pages_values() {
+            for link in &page.external_links {
+                links.push((&page.file.relative, link));
+            }
+        }
+        for section in self.library.sections_values() {
+            for link in &section.external_links {
+                links.push((&section.file.relative, link));
+            }
+        }
+        links.retain(|(_, link)| !link_checker_config.should_skip(link));
+
+        let mut urls: Vec<String> = links.iter().map(|(_, link)| link.to_string()).collect();
+        urls.sort();
+        urls.dedup();
+
+        let broken: HashMap<String, link_checker::LinkResult> =
+            link_checker::check_urls(&urls, link_checker_config)?
+                .into_iter()
+                .filter(|(_, res)| !res.is_valid())
+                .collect();
+
+        let mut broken_links: Vec<BrokenLink> = links
+            .into_iter()
+            .filter_map(|(file, link)| {
+                broken.get(link).map(|res| BrokenLink {
+                    file: file.clone(),
+                    link: link.clone(),
+                    message: res.message(),
+                })
+            })
+            .collect();
+        broken_links.sort();
+        broken_links.dedup();
+        self.broken_links = broken_links;
+
+        if !self.broken_links.is_empty() && !link_checker_config.failures_as_warnings {
+            bail!(self.broken_links_report());
+        }
 
         Ok(())
     }
 
+    /// A report of all the broken external links, grouped by file
+    pub fn broken_links_report(&self) -> String {
+        let num_files = {
+            let mut files: Vec<_> = self.broken_links.iter().map(|b| &b.file).collect();
+            files.dedup();
+            files.len()
+        };
+        let mut report = format!(
+            "Found {} broken external link(s) in {} file(s):",
+            self.broken_links.len(),
+            num_files
+        );
+
+        let mut current_file = None;
+        for broken_link in &self.broken_links {
+            if current_file != Some(&broken_link.file) {
+                report.push_str(&format!("\n{}:", broken_link.file));
+                current_file = Some(&broken_link.file);
+            }
+            report.push_str(&format!("\n  - {}: {}", broken_link.link, broken_link.message));
+        }
+
+        report
+    }
+
     /// Render the markdown of all pages/sections
     /// Used in a build and in `serve` if a shortcode has changed
     pub fn render_markdown(&mut self) -> Result<()> {
@@ -268,13 +580,14 @@ impl Site {
         let permalinks = &self.permalinks;
         let tera = &self.tera;
         let config = &self.config;
+        let imageproc = Some(&*self.imageproc);
 
         // This is needed in the first place because of silly borrow checker
         let mut pages_insert_anchors = HashMap::new();
         for (_, p) in self.library.pages() {
             pages_insert_anchors.insert(
                 p.file.path.clone(),
-                self.find_parent_section_insert_anchor(&p.file.parent.clone()),
+                self.find_parent_section_insert_anchor(&p.file.parent, &p.lang),
             );
         }
 
@@ -285,7 +598,7 @@ impl Site {
             .par_iter_mut()
             .map(|page| {
                 let insert_anchor = pages_insert_anchors[&page.file.path];
-                page.render_markdown(permalinks, tera, config, insert_anchor)
+                page.render_markdown(permalinks, tera, config, insert_anchor, imageproc)
             })
             .collect::<Result<()>>()?;
 
@@ -294,7 +607,14 @@ impl Site {
             .values_mut()
             .collect::<Vec<_>>()
             .par_iter_mut()
-            .map(|section| section.render_markdown(permalinks, tera, config))
+            .map(|section| section.render_markdown(permalinks, tera, config, imageproc))
+            .collect::<Result<()>>()?;
+
+        self.term_sections
+            .values_mut()
+            .collect::<Vec<_>>()
+            .par_iter_mut()
+            .map(|section| section.render_markdown(permalinks, tera, config, imageproc))
             .collect::<Result<()>>()?;
 
         Ok(())
@@ -310,12 +630,27 @@ impl Site {
             "resize_image",
             global_fns::make_resize_image(self.imageproc.clone()),
         );
+        self.tera.register_function(
+            "image_srcset",
+            global_fns::make_image_srcset(self.imageproc.clone()),
+        );
+        self.tera.register_function(
+            "get_image_metadata",
+            global_fns::make_get_image_metadata(
+                self.content_path.clone(),
+                self.static_path.clone(),
+            ),
+        );
     }
 
     pub fn register_tera_global_fns(&mut self) {
         self.tera.register_function("trans", global_fns::make_trans(self.config.clone()));
         self.tera.register_function("get_page", global_fns::make_get_page(&self.library));
         self.tera.register_function("get_section", global_fns::make_get_section(&self.library));
+        self.tera.register_function(
+            "get_series",
+            global_fns::make_get_series(&self.library, &self.config),
+        );
         self.tera.register_function(
             "get_taxonomy",
             global_fns::make_get_taxonomy(&self.taxonomies, &self.library),
@@ -335,10 +670,26 @@ impl Site {
     /// If `true`, it will also render the markdown for that page
     /// Returns the previous page struct if there was one at the same path
     pub fn add_page(&mut self, mut page: Page, render: bool) -> Result<Option<Page>> {
+        if let Some((pattern, section)) =
+            self.find_parent_section_page_path(&page.file.parent, &page.lang)
+        {
+            page.apply_page_path(&pattern, &section, &self.config)?;
+        }
         self.permalinks.insert(page.file.relative.clone(), page.permalink.clone());
         if render {
-            let insert_anchor = self.find_parent_section_insert_anchor(&page.file.parent);
-            page.render_markdown(&self.permalinks, &self.tera, &self.config, insert_anchor)?;
+            // It has just been edited so it's not committed yet
+            if self.config.git_last_modified {
+                page.last_modified = get_file_last_modified(&page.file.path);
+            }
+            let insert_anchor =
+                self.find_parent_section_insert_anchor(&page.file.parent, &page.lang);
+            page.render_markdown(
+                &self.permalinks,
+                &self.tera,
+                &self.config,
+                insert_anchor,
+                Some(&self.imageproc),
+            )?;
         }
         let prev = self.library.remove_page(&page.file.path);
         self.library.insert_page(page);
@@ -353,7 +704,15 @@ impl Site {
     pub fn add_section(&mut self, mut section: Section, render: bool) -> Result<Option<Section>> {
         self.permalinks.insert(section.file.relative.clone(), section.permalink.clone());
         if render {
-            section.render_markdown(&self.permalinks, &self.tera, &self.config)?;
+            if self.config.git_last_modified {
+                section.last_modified = get_file_last_modified(&section.file.path);
+            }
+            section.render_markdown(
+                &self.permalinks,
+                &self.tera,
+                &self.config,
+                Some(&self.imageproc),
+            )?;
         }
         let prev = self.library.remove_section(&section.file.path);
         self.library.insert_section(section);
@@ -361,19 +720,58 @@ impl Site {
         Ok(prev)
     }
 
-    /// Finds the insert_anchor for the parent section of the directory at `path`.
+    /// Finds the insert_anchor for the parent section of the directory at `path`
+    /// in the given language.
     /// Defaults to `AnchorInsert::None` if no parent section found
-    pub fn find_parent_section_insert_anchor(&self, parent_path: &PathBuf) -> InsertAnchor {
-        match self.library.get_section(&parent_path.join("_index.md")) {
+    pub fn find_parent_section_insert_anchor(
+        &self,
+        parent_path: &Path,
+        lang: &str,
+    ) -> InsertAnchor {
+        let parent = if lang.is_empty() || lang == self.config.default_language {
+            parent_path.join("_index.md")
+        } else {
+            parent_path.join(format!("_index.{}.md", lang))
+        };
+        match self.library.get_section(&parent) {
             Some(s) => s.meta.insert_anchor_links,
             None => InsertAnchor::None,
         }
     }
 
+    /// Finds the `page_path` pattern applying to pages in the directory at `path` in the given
+    /// language, along with the path of the section defining it.
+    /// Transparent sections use the pattern of their parent if they don't have one.
+    pub fn find_parent_section_page_path(
+        &self,
+        parent_path: &Path,
+        lang: &str,
+    ) -> Option<(String, String)> {
+        let filename = if lang.is_empty() || lang == self.config.default_language {
+            "_index.md".to_string()
+        } else {
+            format!("_index.{}.md", lang)
+        };
+        let mut dir = parent_path;
+        while let Some(section) = self.library.get_section(dir.join(&filename)) {
+            if let Some(ref page_path) = section.meta.page_path {
+                return Some((page_path.clone(), section.file.components.join("/")));
+            }
+            if !section.meta.transparent {
+                break;
+            }
+            dir = match dir.parent() {
+                Some(d) => d,
+                None => break,
+            };
+        }
+        None
+    }
+
     /// Find out the direct subsections of each subsection if there are some
     /// as well as the pages for each section
     pub fn populate_sections(&mut self) {
-        self.library.populate_sections();
+        self.library.populate_sections(&self.config);
     }
 
     /// Find all the tags and categories if it's asked in the config
@@ -383,6 +781,14 @@ impl Site {
         }
 
         self.taxonomies = find_taxonomies(&self.config, &self.library)?;
+        for taxonomy in &mut self.taxonomies {
+            for item in &mut taxonomy.items {
+                let key = format!("{}/{}", taxonomy.kind.name, item.slug);
+                if let Some(section) = self.term_sections.get(&key) {
+                    item.content = TermContent::from_section(section);
+                }
+            }
+        }
 
         Ok(())
     }
@@ -474,13 +880,42 @@ impl Site {
     /// Deletes the `public` directory and builds the site
     pub fn build(&self) -> Result<()> {
         self.clean()?;
-        // Render aliases first to allow overwriting
+        // Render aliases first to allow overwriting if `path_collisions_as_warnings` is set
         self.render_aliases()?;
         self.render_sections()?;
         self.render_orphan_pages()?;
         self.render_sitemap()?;
-        if self.config.generate_rss {
-            self.render_rss_feed(self.library.pages_values(), None)?;
+        if self.config.generate_rss || self.config.generate_atom {
+            let pages: Vec<_> = if self.config.is_multilingual() {
+                self.library
+                    .pages_values()
+                    .iter()
+                    .filter(|p| p.lang == self.config.default_language)
+                    .cloned()
+                    .collect()
+            } else {
+                self.library.pages_values()
+            };
+            if self.config.generate_rss {
+                self.render_rss_feed(pages.clone(), None)?;
+            }
+            if self.config.generate_atom {
+                self.render_atom_feed(pages, None)?;
+            }
+        }
+
+        for lang in &self.config.languages {
+            if !lang.rss {
+                continue;
+            }
+            let pages = self
+                .library
+                .pages_values()
+                .iter()
+                .filter(|p| p.lang == lang.code)
+                .cloned()
+                .collect();
+            self.render_feeds(pages, Some(&PathBuf::from(lang.code.clone())))?;
         }
         self.render_404()?;
         self.render_robots()?;
@@ -517,6 +952,18 @@ impl Site {
             ),
         )?;
 
+        for language in &self.config.languages {
+            if language.search {
+                create_file(
+                    &self.output_path.join(format!("search_index.{}.js", language.code)),
+                    &format!(
+                        "window.searchIndex = {};",
+                        search::build_index(&language.code, &self.library)?
+                    ),
+                )?;
+            }
+        }
+
         // then elasticlunr.min.js
         create_file(&self.output_path.join("elasticlunr.min.js"), search::ELASTICLUNR_JS)?;
 
@@ -670,7 +1117,7 @@ impl Site {
             .par_iter()
             .map(|item| {
                 if taxonomy.kind.rss {
-                    self.render_rss_feed(
+                    self.render_feeds(
                         item.pages.iter().map(|p| self.library.get_page_by_key(*p)).collect(),
                         Some(&PathBuf::from(format!("{}/{}", taxonomy.kind.name, item.slug))),
                     )?;
@@ -696,18 +1143,55 @@ impl Site {
     pub fn render_sitemap(&self) -> Result<()> {
         ensure_directory_exists(&self.output_path)?;
 
+        if self.config.languages.is_empty() {
+            let sitemap = self.render_sitemap_for_lang(None)?;
+            return create_file(&self.output_path.join("sitemap.xml"), &sitemap);
+        }
+
+        // With several languages, each one gets its own sitemap and `sitemap.xml` is an index.
+        // The default language content is at the root so its sitemap has to be there as well
+        // to be allowed to list it
+        let mut sitemaps = vec![];
+        let default_language = &self.config.default_language;
+        let sitemap = self.render_sitemap_for_lang(Some(default_language))?;
+        let filename = format!("sitemap_{}.xml", default_language);
+        create_file(&self.output_path.join(&filename), &sitemap)?;
+        sitemaps.push(self.config.make_permalink(&filename));
+        for code in self.config.languages.iter().map(|l| &l.code) {
+            let sitemap = self.render_sitemap_for_lang(Some(code))?;
+            let output_path = self.output_path.join(code);
+            ensure_directory_exists(&output_path)?;
+            create_file(&output_path.join("sitemap.xml"), &sitemap)?;
+            sitemaps.push(self.config.make_permalink(&format!("{}/sitemap.xml", code)));
+        }
+
+        let mut context = Context::new();
+        context.insert("sitemaps", &sitemaps);
+        context.insert("config", &self.config);
+        let index = render_template("sitemap_index.xml", &self.tera, &context, &self.config.theme)?;
+        create_file(&self.output_path.join("sitemap.xml"), &index)?;
+
+        Ok(())
+    }
+
+    /// Renders the sitemap of the content in the given language, or all the content if `None`.
+    /// Taxonomies are only in the sitemap of the default language
+    fn render_sitemap_for_lang(&self, lang: Option<&String>) -> Result<String> {
         let mut context = Context::new();
+        let is_included = |content_lang: &String| match lang {
+            Some(l) => l == content_lang,
+            None => true,
+        };
 
+        let now = self.config.build_datetime();
         let mut pages = self
             .library
             .pages_values()
             .iter()
-            .filter(|p| !p.is_draft())
+            .filter(|p| !p.is_draft() && !p.is_expired(now) && is_included(&p.lang))
             .map(|p| {
-                let date = match p.meta.date {
-                    Some(ref d) => Some(d.to_string()),
-                    None => None,
-                };
+                // The last modification is the `updated` date if there is one
+                let date = p.meta.updated.as_ref().or(p.meta.date.as_ref()).map(|d| d.to_string());
                 SitemapEntry::new(p.permalink.clone(), date)
             })
             .collect::<Vec<_>>();
@@ -718,34 +1202,43 @@ impl Site {
             .library
             .sections_values()
             .iter()
+            .filter(|s| is_included(&s.lang))
             .map(|s| SitemapEntry::new(s.permalink.clone(), None))
             .collect::<Vec<_>>();
         sections.sort_by(|a, b| a.permalink.cmp(&b.permalink));
         context.insert("sections", &sections);
 
         let mut taxonomies = vec![];
-        for taxonomy in &self.taxonomies {
-            let name = &taxonomy.kind.name;
-            let mut terms = vec![];
-            terms.push(SitemapEntry::new(self.config.make_permalink(name), None));
-            for item in &taxonomy.items {
-                terms.push(SitemapEntry::new(
-                    self.config.make_permalink(&format!("{}/{}", &name, item.slug)),
-                    None,
-                ));
+        if is_included(&self.config.default_language) {
+            for taxonomy in &self.taxonomies {
+                let name = &taxonomy.kind.name;
+                let mut terms = vec![];
+                terms.push(SitemapEntry::new(self.config.make_permalink(name), None));
+                for item in &taxonomy.items {
+                    terms.push(SitemapEntry::new(
+                        self.config.make_permalink(&format!("{}/{}", &name, item.slug)),
+                        None,
+                    ));
+                }
+                terms.sort_by(|a, b| a.permalink.cmp(&b.permalink));
+                taxonomies.push(terms);
             }
-            terms.sort_by(|a, b| a.permalink.cmp(&b.permalink));
-            taxonomies.push(terms);
         }
         context.insert("taxonomies", &taxonomies);
 
         context.insert("config", &self.config);
+        context.insert("lang", &lang);
 
-        let sitemap = &render_template("sitemap.xml", &self.tera, &context, &self.config.theme)?;
-
-        create_file(&self.output_path.join("sitemap.xml"), sitemap)?;
+        render_template("sitemap.xml", &self.tera, &context, &self.config.theme)
+    }
 
-        Ok(())
+    /// Renders the feeds of a subset of the site (a language, a section, a taxonomy term...):
+    /// the RSS one is always rendered and the Atom one only if `generate_atom` is set
+    pub fn render_feeds(&self, all_pages: Vec<&Page>, base_path: Option<&PathBuf>) -> Result<()> {
+        if self.config.generate_atom {
+            self.render_atom_feed(all_pages.clone(), base_path)?;
+        }
+        self.render_rss_feed(all_pages, base_path)
     }
 
     /// Renders a RSS feed for the given path and at the given path
@@ -755,43 +1248,73 @@ impl Site {
         &self,
         all_pages: Vec<&Page>,
         base_path: Option<&PathBuf>,
+    ) -> Result<()> {
+        self.render_feed(all_pages, base_path, "rss.xml")
+    }
+
+    /// Renders an Atom feed for the given path and at the given path
+    /// If both arguments are `None`, it will render only the Atom feed for the whole
+    /// site at the root folder.
+    pub fn render_atom_feed(
+        &self,
+        all_pages: Vec<&Page>,
+        base_path: Option<&PathBuf>,
+    ) -> Result<()> {
+        self.render_feed(all_pages, base_path, "atom.xml")
+    }
+
+    /// Renders the feed using the template of the same name as the file created
+    fn render_feed(
+        &self,
+        all_pages: Vec<&Page>,
+        base_path: Option<&PathBuf>,
+        filename: &str,
     ) -> Result<()> {
         ensure_directory_exists(&self.output_path)?;
 
         let mut context = Context::new();
+        let now = self.config.build_datetime();
         let mut pages = all_pages
             .into_iter()
-            .filter(|p| p.meta.date.is_some() && !p.is_draft())
+            .filter(|p| p.meta.date.is_some() && !p.is_draft() && !p.is_expired(now))
             .collect::<Vec<_>>();
 
-        // Don't generate a RSS feed if none of the pages has a date
+        // Don't generate a feed if none of the pages has a date
         if pages.is_empty() {
             return Ok(());
         }
 
         pages.par_sort_unstable_by(sort_actual_pages_by_date);
 
-        context.insert("last_build_date", &pages[0].meta.date.clone().map(|d| d.to_string()));
+        context.insert("last_build_date", &pages[0].meta.datetime_utc.map(to_rfc3339));
         // limit to the last n elements if the limit is set; otherwise use all.
         let num_entries = self.config.rss_limit.unwrap_or(pages.len());
-        let p = pages
+        let pages = &pages[..num_entries.min(pages.len())];
+        // An edit to an older page can be the latest change of the feed
+        let last_updated = pages
             .iter()
-            .take(num_entries)
-            .map(|x| x.to_serialized_basic(&self.library))
-            .collect::<Vec<_>>();
+            .filter_map(|p| p.meta.updated_datetime_utc.or(p.meta.datetime_utc))
+            .max()
+            .map(to_rfc3339);
+        context.insert("last_updated", &last_updated);
+        let p = pages.iter().map(|x| x.to_serialized_basic(&self.library)).collect::<Vec<_>>();
 
         context.insert("pages", &p);
         context.insert("config", &self.config);
+        // All pages of a feed are in the same language unless it is a taxonomy one
+        let lang =
+            if pages[0].lang.is_empty() { &self.config.default_language } else { &pages[0].lang };
+        context.insert("lang", lang);
 
-        let rss_feed_url = if let Some(ref base) = base_path {
-            self.config.make_permalink(&base.join("rss.xml").to_string_lossy().replace('\\', "/"))
+        let feed_url = if let Some(ref base) = base_path {
+            self.config.make_permalink(&base.join(filename).to_string_lossy().replace('\\', "/"))
         } else {
-            self.config.make_permalink("rss.xml")
+            self.config.make_permalink(filename)
         };
 
-        context.insert("feed_url", &rss_feed_url);
+        context.insert("feed_url", &feed_url);
 
-        let feed = &render_template("rss.xml", &self.tera, &context, &self.config.theme)?;
+        let feed = &render_template(filename, &self.tera, &context, &self.config.theme)?;
 
         if let Some(ref base) = base_path {
             let mut output_path = self.output_path.clone().to_path_buf();
@@ -801,9 +1324,9 @@ impl Site {
                     create_directory(&output_path)?;
                 }
             }
-            create_file(&output_path.join("rss.xml"), feed)?;
+            create_file(&output_path.join(filename), feed)?;
         } else {
-            create_file(&self.output_path.join("rss.xml"), feed)?;
+            create_file(&self.output_path.join(filename), feed)?;
         }
 
         Ok(())
@@ -815,6 +1338,14 @@ impl Site {
         let public = self.output_path.clone();
 
         let mut output_path = public.to_path_buf();
+        // Sections in other languages than the default one are rendered in a `{LANG}` folder
+        if !section.lang.is_empty() && section.lang != self.config.default_language {
+            output_path.push(&section.lang);
+            if !output_path.exists() {
+                create_directory(&output_path)?;
+            }
+        }
+
         for component in &section.file.components {
             output_path.push(component);
 
@@ -841,6 +1372,13 @@ impl Site {
             return Ok(());
         }
 
+        if section.meta.generate_rss {
+            self.render_feeds(
+                section.pages.iter().map(|k| self.library.get_page_by_key(*k)).collect(),
+                Some(&PathBuf::from(section.path.trim_matches('/'))),
+            )?;
+        }
+
         if let Some(ref redirect_to) = section.meta.redirect_to {
             let permalink = self.config.make_permalink(redirect_to);
             create_file(
@@ -1,3 +1,4 @@
+extern crate config;
 extern crate site;
 extern crate tempfile;
 
@@ -7,6 +8,7 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::path::Path;
 
+use config::AtomContent;
 use site::Site;
 use tempfile::tempdir;
 
@@ -18,7 +20,7 @@ fn can_parse_site() {
     site.load().unwrap();
 
     // Correct number of pages (sections do not count as pages)
-    assert_eq!(site.library.pages().len(), 22);
+    assert_eq!(site.library.pages().len(), 24);
     let posts_path = path.join("content").join("posts");
 
     // Make sure the page with a url doesn't have any sections
@@ -31,11 +33,11 @@ fn can_parse_site() {
     assert_eq!(asset_folder_post.file.components, vec!["posts".to_string()]);
 
     // That we have the right number of sections
-    assert_eq!(site.library.sections().len(), 11);
+    assert_eq!(site.library.sections().len(), 13);
 
     // And that the sections are correct
     let index_section = site.library.get_section(&path.join("content").join("_index.md")).unwrap();
-    assert_eq!(index_section.subsections.len(), 4);
+    assert_eq!(index_section.subsections.len(), 5);
     assert_eq!(index_section.pages.len(), 1);
     assert!(index_section.ancestors.is_empty());
 
@@ -115,6 +117,44 @@ macro_rules! file_contains {
     }};
 }
 
+#[test]
+fn can_use_page_path_of_sections() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+
+    // The pattern is inherited by the pages of transparent subsections
+    let archive_path = path.join("content").join("archive");
+    let page = site.library.get_page(&archive_path.join("2017").join("old-post.md")).unwrap();
+    assert_eq!(page.path, "archive/2017/04/old-post/");
+    assert_eq!(page.permalink, "https://replace-this-with-your-url.com/archive/2017/04/old-post/");
+    assert_eq!(site.permalinks["archive/2017/old-post.md"], page.permalink);
+
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+    assert!(file_exists!(public, "archive/2017/04/old-post/index.html"));
+    assert!(file_contains!(public, "sitemap.xml", "archive/2017/04/old-post/"));
+}
+
+#[test]
+fn can_set_last_modified_dates() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+    let posts_path = path.join("content").join("posts");
+    assert!(site.library.get_page(&posts_path.join("simple.md")).unwrap().last_modified.is_none());
+
+    site.config.git_last_modified = true;
+    site.load().unwrap();
+    assert!(site.library.get_page(&posts_path.join("simple.md")).unwrap().last_modified.is_some());
+    let posts_section = site.library.get_section(&posts_path.join("_index.md")).unwrap();
+    assert!(posts_section.last_modified.is_some());
+}
+
 #[test]
 fn can_build_site_without_live_reload() {
     let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
@@ -173,6 +213,10 @@ fn can_build_site_without_live_reload() {
     assert_eq!(file_exists!(public, "categories/index.html"), true);
     assert_eq!(file_exists!(public, "categories/a-category/index.html"), true);
     assert_eq!(file_exists!(public, "categories/a-category/rss.xml"), true);
+    // with the content of their `_index.md`
+    assert!(file_contains!(public, "categories/a-category/index.html", "Title: A category"));
+    assert!(file_contains!(public, "categories/a-category/index.html", "Icon: hammer"));
+    assert!(file_contains!(public, "categories/a-category/index.html", "<strong>rebuilt</strong>"));
     // But no tags
     assert_eq!(file_exists!(public, "tags/index.html"), false);
 
@@ -206,7 +250,10 @@ fn can_build_site_without_live_reload() {
         "sitemap.xml",
         "<loc>https://replace-this-with-your-url.com/posts/</loc>"
     ));
-    // Drafts are not in the sitemap
+    // The `updated` date is used as last modification date if present
+    assert!(file_contains!(public, "sitemap.xml", "<lastmod>2018-02-01</lastmod>"));
+    // Drafts are not rendered by default
+    assert_eq!(file_exists!(public, "posts/draft/index.html"), false);
     assert!(!file_contains!(public, "sitemap.xml", "draft"));
 
     // robots.txt has been rendered from the template
@@ -264,7 +311,172 @@ fn can_build_site_with_live_reload() {
         "posts/python/index.html",
         r#"<a name="continue-reading"></a>"#
     ));
+}
+
+#[test]
+fn can_build_site_with_drafts() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.include_drafts();
+    site.load().unwrap();
+    assert_eq!(site.library.pages().len(), 25);
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
     assert!(file_contains!(public, "posts/draft/index.html", r#"THEME_SHORTCODE"#));
+    // Drafts are rendered but not listed anywhere
+    let posts = site.library.get_section(&path.join("content/posts/_index.md")).unwrap();
+    assert!(!posts.pages.iter().any(|k| site.library.get_page_by_key(*k).is_draft()));
+    assert!(!file_contains!(public, "sitemap.xml", "draft"));
+    assert!(!file_contains!(public, "rss.xml", "posts/draft/"));
+    assert!(!file_contains!(public, "posts/rss.xml", "posts/draft/"));
+}
+
+#[test]
+fn can_build_site_with_series() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
+    // The series spans several sections, numbered parts first
+    let nix = site.library.get_page(&path.join("content/posts/tutorials/devops/nix.md")).unwrap();
+    let series: Vec<_> =
+        nix.series.iter().map(|k| site.library.get_page_by_key(*k).file.relative.clone()).collect();
+    assert_eq!(
+        series,
+        vec![
+            "posts/tutorials/devops/nix.md",
+            "posts/tutorials/programming/python.md",
+            "posts/python.md"
+        ]
+    );
+
+    assert!(file_contains!(public, "posts/tutorials/devops/nix/index.html", "Part 1 of 3"));
+    assert!(!file_contains!(public, "posts/tutorials/devops/nix/index.html", "Previous part"));
+    assert!(file_contains!(
+        public,
+        "posts/tutorials/devops/nix/index.html",
+        "Next part: https://replace-this-with-your-url.com/posts/tutorials/programming/python/"
+    ));
+    assert!(file_contains!(public, "posts/tutorials/programming/python/index.html", "Part 2 of 3"));
+    assert!(file_contains!(
+        public,
+        "posts/tutorials/programming/python/index.html",
+        "Previous part: https://replace-this-with-your-url.com/posts/tutorials/devops/nix/"
+    ));
+    assert!(file_contains!(public, "posts/python/index.html", "Part 3 of 3"));
+    assert!(!file_contains!(public, "posts/python/index.html", "Next part"));
+    // get_series works
+    assert!(file_contains!(
+        public,
+        "posts/python/index.html",
+        "Series page: https://replace-this-with-your-url.com/posts/tutorials/devops/nix/"
+    ));
+    // Pages outside of a series don't have one
+    assert!(!file_contains!(public, "posts/simple/index.html", "Part"));
+}
+
+#[test]
+fn can_build_site_with_ancestor_sections() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
+    // Ancestors go from the root section down to the direct parent
+    assert!(file_contains!(
+        public,
+        "posts/tutorials/programming/python/index.html",
+        "Breadcrumb:  (https://replace-this-with-your-url.com/)Breadcrumb: Posts (https://replace-this-with-your-url.com/posts/)Breadcrumb: Tutorials (https://replace-this-with-your-url.com/posts/tutorials/)Breadcrumb: Programming (https://replace-this-with-your-url.com/posts/tutorials/programming/)"
+    ));
+    assert!(file_contains!(
+        public,
+        "posts/tutorials/programming/python/index.html",
+        "Parent section: Programming"
+    ));
+    assert!(file_contains!(
+        public,
+        "posts/tutorials/programming/index.html",
+        "Parent section: https://replace-this-with-your-url.com/posts/tutorials/"
+    ));
+    // The index section has no parent
+    assert!(!file_contains!(public, "index.html", "Parent section"));
+}
+
+#[test]
+fn can_build_site_with_backlinks() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
+    let python = site.library.get_page(&path.join("content/posts/python.md")).unwrap();
+    assert_eq!(python.backlinks.len(), 2);
+    // Sorted by permalink, pages and sections alike
+    assert!(file_contains!(
+        public,
+        "posts/python/index.html",
+        "Referenced by: Simple article with shortcodes (https://replace-this-with-your-url.com/posts/simple/)Referenced by: Tutorials (https://replace-this-with-your-url.com/posts/tutorials/)"
+    ));
+    assert!(!file_contains!(public, "posts/simple/index.html", "Referenced by"));
+}
+
+#[test]
+fn can_build_site_with_scheduled_pages() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
+    // Future pages are published by default
+    assert!(file_exists!(public, "posts/scheduled/index.html"));
+    assert!(file_contains!(public, "sitemap.xml", "posts/scheduled/"));
+
+    // Expired pages are rendered but not listed anywhere
+    let posts_path = path.join("content").join("posts");
+    let expired = site.library.get_page(&posts_path.join("expired.md")).unwrap();
+    assert!(expired.is_expired(site.config.build_datetime()));
+    let posts_section = site.library.get_section(&posts_path.join("_index.md")).unwrap();
+    assert!(posts_section
+        .pages
+        .iter()
+        .all(|k| site.library.get_page_by_key(*k).file.path != expired.file.path));
+    assert!(file_exists!(public, "posts/expired/index.html"));
+    assert!(!file_contains!(public, "sitemap.xml", "posts/expired/"));
+    assert!(!file_contains!(public, "rss.xml", "posts/expired/"));
+
+    // Future pages are left out entirely with `build_future = false`
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.config.build_future = false;
+    site.load().unwrap();
+    assert!(site.library.get_page(&posts_path.join("scheduled.md")).is_none());
+
+    // Unless `--future` is passed
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.config.build_future = false;
+    site.include_future_pages();
+    site.load().unwrap();
+    assert!(site.library.get_page(&posts_path.join("scheduled.md")).is_some());
 }
 
 #[test]
@@ -285,6 +497,7 @@ fn can_build_site_with_taxonomies() {
         };
     }
     site.populate_taxonomies().unwrap();
+    site.register_tera_global_fns();
     let tmp_dir = tempdir().expect("create temp dir");
     let public = &tmp_dir.path().join("public");
     site.set_output_path(&public);
@@ -336,6 +549,131 @@ fn can_build_site_with_taxonomies() {
     ));
 }
 
+#[test]
+fn can_build_site_with_merged_taxonomy_terms() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+
+    for page in site.library.pages_mut().values_mut() {
+        let category = match page.file.relative.as_str() {
+            "posts/simple.md" => "Rust",
+            "posts/python.md" => "rust",
+            _ => continue,
+        };
+        let mut taxonomies = HashMap::new();
+        taxonomies.insert("categories".to_string(), vec![category.to_string()]);
+        page.meta.taxonomies = taxonomies;
+    }
+    site.populate_taxonomies().unwrap();
+    site.register_tera_global_fns();
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
+    // Both spellings link to the merged term
+    assert!(file_contains!(
+        public,
+        "posts/simple/index.html",
+        "Category: https://replace-this-with-your-url.com/categories/rust/"
+    ));
+    assert!(file_contains!(
+        public,
+        "posts/python/index.html",
+        "Category: https://replace-this-with-your-url.com/categories/rust/"
+    ));
+}
+
+#[test]
+fn can_build_site_with_hierarchical_taxonomies() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+    site.config.taxonomies[0].hierarchical = true;
+
+    for (i, (_, page)) in site.library.pages_mut().iter_mut().enumerate() {
+        page.meta.taxonomies = {
+            let mut taxonomies = HashMap::new();
+            taxonomies.insert(
+                "categories".to_string(),
+                vec![if i % 2 == 0 { "A/B" } else { "A/C" }.to_string()],
+            );
+            taxonomies
+        };
+    }
+    site.populate_taxonomies().unwrap();
+    site.register_tera_global_fns();
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
+    assert_eq!(site.taxonomies[0].items.len(), 3);
+    assert!(file_exists!(public, "categories/a/index.html"));
+    assert!(file_exists!(public, "categories/a/b/index.html"));
+    assert!(file_exists!(public, "categories/a/c/index.html"));
+    assert!(file_exists!(public, "categories/a/b/rss.xml"));
+    // The children are available to the templates
+    assert!(file_contains!(
+        public,
+        "categories/a/index.html",
+        "Child: B (A/B) https://replace-this-with-your-url.com/categories/a/b/"
+    ));
+    assert!(file_contains!(
+        public,
+        "categories/a/index.html",
+        "Child: C (A/C) https://replace-this-with-your-url.com/categories/a/c/"
+    ));
+    assert!(file_contains!(
+        public,
+        "sitemap.xml",
+        "<loc>https://replace-this-with-your-url.com/categories/a/b/</loc>"
+    ));
+}
+
+#[test]
+fn can_build_site_with_related_pages() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+
+    site.config.related_pages.taxonomies.insert("categories".to_string(), 1.0);
+    for page in site.library.pages_mut().values_mut() {
+        let category = match page.file.relative.as_str() {
+            "posts/simple.md" | "posts/python.md" => "Code",
+            "posts/fixed-slug.md" => "Life",
+            _ => continue,
+        };
+        let mut taxonomies = HashMap::new();
+        taxonomies.insert("categories".to_string(), vec![category.to_string()]);
+        page.meta.taxonomies = taxonomies;
+    }
+    site.populate_sections();
+    site.populate_taxonomies().unwrap();
+    site.register_tera_global_fns();
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
+    assert!(file_contains!(
+        public,
+        "posts/simple/index.html",
+        "Related: https://replace-this-with-your-url.com/posts/python/"
+    ));
+    assert!(file_contains!(
+        public,
+        "posts/python/index.html",
+        "Related: https://replace-this-with-your-url.com/posts/simple/"
+    ));
+    // Nothing shares its category
+    assert!(!file_contains!(public, "posts/something-else/index.html", "Related:"));
+}
+
 #[test]
 fn can_build_site_and_insert_anchor_links() {
     let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
@@ -529,6 +867,130 @@ fn can_build_rss_feed() {
     assert!(file_contains!(public, "rss.xml", "Extra Syntax"));
     // Next is posts/simple.md
     assert!(file_contains!(public, "rss.xml", "Simple article with shortcodes"));
+
+    // The posts section asks for its own feed, which includes the transparent sections pages
+    assert!(file_exists!(public, "posts/rss.xml"));
+    assert!(file_contains!(
+        public,
+        "posts/rss.xml",
+        "https://replace-this-with-your-url.com/posts/rss.xml"
+    ));
+    assert!(file_contains!(public, "posts/rss.xml", "Extra Syntax"));
+    assert!(file_contains!(public, "posts/rss.xml", "A transparent page"));
+    // Other sections don't
+    assert_eq!(file_exists!(public, "posts/tutorials/rss.xml"), false);
+}
+
+#[test]
+fn can_build_atom_feed() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+    site.config.generate_atom = true;
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
+    assert!(file_exists!(public, "rss.xml"));
+    assert!(file_exists!(public, "atom.xml"));
+    assert!(file_contains!(
+        public,
+        "atom.xml",
+        "<id>https://replace-this-with-your-url.com/atom.xml</id>"
+    ));
+    // latest article is posts/extra-syntax.md
+    assert!(file_contains!(public, "atom.xml", "Extra Syntax"));
+    assert!(file_contains!(
+        public,
+        "atom.xml",
+        "<id>https://replace-this-with-your-url.com/posts/simple/</id>"
+    ));
+    assert!(file_contains!(public, "atom.xml", "<name>My site</name>"));
+    // Entries use the `updated` date when there is one
+    assert!(file_contains!(public, "atom.xml", "<updated>2018-02-01T00:00:00+00:00</updated>"));
+    // So do sections with `generate_rss = true`
+    assert!(file_exists!(public, "posts/atom.xml"));
+    // Taxonomies with `rss = true` get an Atom feed as well
+    assert!(file_exists!(public, "categories/a-category/rss.xml"));
+    assert!(file_exists!(public, "categories/a-category/atom.xml"));
+    // Full content only by default
+    assert!(file_contains!(public, "atom.xml", "<content type=\"html\">"));
+    assert!(!file_contains!(public, "atom.xml", "<summary"));
+}
+
+#[test]
+fn does_not_build_feeds_of_sections_not_rendered() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+    site.library
+        .get_section_mut(&path.join("content").join("posts").join("_index.md"))
+        .unwrap()
+        .meta
+        .render = false;
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
+    assert!(file_exists!(public, "posts/python/index.html"));
+    assert!(!file_exists!(public, "posts/rss.xml"));
+}
+
+#[test]
+fn can_build_atom_feed_with_summaries() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+    site.config.generate_atom = true;
+    site.config.atom_content = AtomContent::Summary;
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
+    // posts/python.md has a summary
+    assert!(file_contains!(
+        public,
+        "atom.xml",
+        "<summary type=\"html\">&lt;p&gt;Same filename but different path&lt;&#x2F;p&gt;"
+    ));
+    // and the pages without one still have their content
+    assert!(file_contains!(public, "atom.xml", "<content type=\"html\">"));
+}
+
+#[test]
+fn can_build_atom_feed_with_datetimes_without_offset() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+    site.config.generate_atom = true;
+
+    for page in site.library.pages_mut().values_mut() {
+        match page.file.relative.as_str() {
+            "posts/simple.md" => page.meta.date = Some("2017-04-01T10:00:00".to_string()),
+            "posts/python.md" => page.meta.updated = Some("2200-01-01T08:30:00".to_string()),
+            _ => continue,
+        };
+        page.meta.date_to_datetime();
+    }
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
+    assert!(file_contains!(public, "atom.xml", "<published>2017-04-01T10:00:00+00:00</published>"));
+    // The feed was last updated when an older page was edited
+    assert!(file_contains!(
+        public,
+        "atom.xml",
+        "Zola</generator>\n    <updated>2200-01-01T08:30:00+00:00</updated>"
+    ));
 }
 
 #[test]
@@ -611,3 +1073,72 @@ fn can_apply_page_templates() {
     assert_eq!(child.meta.template, Some("page_template_child.html".into()));
     assert_eq!(child.meta.title, Some("Local section override".into()));
 }
+
+#[test]
+fn reports_all_broken_external_links() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+
+    let posts_path = path.join("content").join("posts").join("_index.md");
+    site.library.get_section_mut(&posts_path).unwrap().external_links = vec![
+        "https://google.comyz".to_string(),
+        "https://google.comyx".to_string(),
+        "https://skipped.comyx/hello".to_string(),
+    ];
+    site.config.link_checker.skip_prefixes = vec!["https://skipped.comyx".to_string()];
+
+    let res = site.check_external_links();
+    assert!(res.is_err());
+    let err = res.unwrap_err().to_string();
+    assert!(err.starts_with("Found 2 broken external link(s) in 1 file(s):\nposts/_index.md:\n"));
+    assert!(err.contains("\n  - https://google.comyx: "));
+    assert!(err.contains("\n  - https://google.comyz: "));
+    assert!(!err.contains("skipped"));
+    assert_eq!(site.broken_links.len(), 2);
+
+    // Only a warning
+    site.config.link_checker.failures_as_warnings = true;
+    assert!(site.check_external_links().is_ok());
+    assert_eq!(site.broken_links.len(), 2);
+    assert_eq!(site.broken_links_report(), err);
+}
+
+#[test]
+fn reports_path_collisions() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+    assert!(site.path_collisions.is_empty());
+
+    for page in site.library.pages_mut().values_mut() {
+        if page.file.relative == "posts/python.md" {
+            page.meta.aliases = vec!["/posts/simple/".to_string()];
+        }
+        if page.file.relative == "posts/fixed-slug.md" {
+            page.path = "posts/".to_string();
+        }
+    }
+
+    let res = site.check_path_collisions();
+    assert!(res.is_err());
+    let err = res.unwrap_err().to_string();
+    assert_eq!(
+        err,
+        "Found 2 output path collision(s):\n\
+         posts/index.html would be rendered by:\n  \
+         - page `posts/fixed-slug.md`\n  \
+         - section `posts/_index.md`\n\
+         posts/simple/index.html would be rendered by:\n  \
+         - alias `/posts/simple/` of page `posts/python.md`\n  \
+         - page `posts/simple.md`"
+    );
+
+    // Only a warning
+    site.config.path_collisions_as_warnings = true;
+    assert!(site.check_path_collisions().is_ok());
+    assert_eq!(site.path_collisions.len(), 2);
+    assert_eq!(site.path_collisions_report(), err);
+}
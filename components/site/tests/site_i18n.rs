@@ -0,0 +1,166 @@
+extern crate site;
+extern crate tempfile;
+
+use std::env;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+
+use site::Site;
+use tempfile::tempdir;
+
+macro_rules! file_exists {
+    ($root: expr, $path: expr) => {{
+        let mut path = $root.clone();
+        for component in $path.split("/") {
+            path = path.join(component);
+        }
+        Path::new(&path).exists()
+    }};
+}
+
+macro_rules! file_contains {
+    ($root: expr, $path: expr, $text: expr) => {{
+        let mut path = $root.clone();
+        for component in $path.split("/") {
+            path = path.join(component);
+        }
+        let mut file = File::open(&path).unwrap();
+        let mut s = String::new();
+        file.read_to_string(&mut s).unwrap();
+        println!("{}", s);
+        s.contains($text)
+    }};
+}
+
+#[test]
+fn can_parse_multilingual_site() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site_i18n");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+
+    assert_eq!(site.library.pages().len(), 3);
+    // 2 index sections given + the default `it` one + 2 blog sections
+    assert_eq!(site.library.sections().len(), 5);
+
+    // default index sections
+    let default_index_section =
+        site.library.get_section(&path.join("content").join("_index.md")).unwrap();
+    assert_eq!(default_index_section.pages.len(), 0);
+    assert!(default_index_section.ancestors.is_empty());
+    assert_eq!(default_index_section.translations.len(), 2);
+
+    let fr_index_section =
+        site.library.get_section(&path.join("content").join("_index.fr.md")).unwrap();
+    assert_eq!(fr_index_section.pages.len(), 0);
+    assert!(fr_index_section.ancestors.is_empty());
+    assert_eq!(fr_index_section.permalink, "https://example.com/fr/");
+
+    // blog sections get only their own language pages
+    let blog_path = path.join("content").join("blog");
+    let default_blog = site.library.get_section(&blog_path.join("_index.md")).unwrap();
+    assert_eq!(default_blog.subsections.len(), 0);
+    assert_eq!(default_blog.pages.len(), 2);
+    assert_eq!(
+        default_blog.ancestors,
+        vec![*site.library.get_section_key(&default_index_section.file.path).unwrap()]
+    );
+    for key in &default_blog.pages {
+        let page = site.library.get_page_by_key(*key);
+        assert_eq!(page.lang, "en");
+    }
+
+    let fr_blog = site.library.get_section(&blog_path.join("_index.fr.md")).unwrap();
+    assert_eq!(fr_blog.subsections.len(), 0);
+    assert_eq!(fr_blog.pages.len(), 1);
+    assert_eq!(
+        fr_blog.ancestors,
+        vec![*site.library.get_section_key(&fr_index_section.file.path).unwrap()]
+    );
+    assert_eq!(fr_blog.translations.len(), 1);
+    for key in &fr_blog.pages {
+        let page = site.library.get_page_by_key(*key);
+        assert_eq!(page.lang, "fr");
+        assert_eq!(page.translations.len(), 1);
+    }
+}
+
+#[test]
+fn can_build_multilingual_site() {
+    let mut path = env::current_dir().unwrap().parent().unwrap().parent().unwrap().to_path_buf();
+    path.push("test_site_i18n");
+    let mut site = Site::new(&path, "config.toml").unwrap();
+    site.load().unwrap();
+    let tmp_dir = tempdir().expect("create temp dir");
+    let public = &tmp_dir.path().join("public");
+    site.set_output_path(&public);
+    site.build().unwrap();
+
+    assert!(public.exists());
+
+    // Index pages
+    assert!(file_exists!(public, "index.html"));
+    assert!(file_exists!(public, "fr/index.html"));
+    assert!(file_exists!(public, "it/index.html"));
+    assert!(file_contains!(public, "fr/index.html", "Language: fr"));
+    assert!(file_contains!(
+        public,
+        "index.html",
+        "Translated in fr: Accueil https://example.com/fr/"
+    ));
+
+    // Sections are there as well, with translations info
+    assert!(file_exists!(public, "blog/index.html"));
+    assert!(file_exists!(public, "fr/blog/index.html"));
+    assert!(file_contains!(public, "fr/blog/index.html", "Language: fr"));
+    assert!(file_contains!(public, "fr/blog/index.html", "Quelque chose"));
+    assert!(!file_contains!(public, "fr/blog/index.html", "Something else"));
+    assert!(file_contains!(
+        public,
+        "blog/index.html",
+        "Translated in fr: Blog https://example.com/fr/blog/"
+    ));
+
+    // Pages and translations
+    assert!(file_exists!(public, "blog/something/index.html"));
+    assert!(file_exists!(public, "fr/blog/something/index.html"));
+    assert!(file_contains!(public, "blog/something/index.html", "Language: en"));
+    assert!(file_contains!(
+        public,
+        "blog/something/index.html",
+        "Translated in fr: Quelque chose https://example.com/fr/blog/something/"
+    ));
+    assert!(file_contains!(
+        public,
+        "fr/blog/something/index.html",
+        "Translated in en: Something https://example.com/blog/something/"
+    ));
+
+    // RSS feeds: one for the default language and one per language that asks for it
+    assert!(file_exists!(public, "rss.xml"));
+    assert!(file_contains!(public, "rss.xml", "https://example.com/blog/something-else/"));
+    assert!(!file_contains!(public, "rss.xml", "https://example.com/fr/blog/something/"));
+    assert!(file_exists!(public, "fr/rss.xml"));
+    assert!(file_contains!(public, "fr/rss.xml", "<language>fr</language>"));
+    assert!(!file_contains!(public, "fr/rss.xml", "https://example.com/blog/something-else/"));
+    assert!(!file_exists!(public, "it/rss.xml"));
+
+    // Search index per language
+    assert!(file_exists!(public, "search_index.en.js"));
+    assert!(file_exists!(public, "search_index.fr.js"));
+    assert!(!file_exists!(public, "search_index.it.js"));
+
+    // One sitemap per language and an index of them
+    assert!(file_exists!(public, "sitemap.xml"));
+    assert!(file_contains!(public, "sitemap.xml", "<sitemapindex"));
+    assert!(file_contains!(public, "sitemap.xml", "https://example.com/sitemap_en.xml"));
+    assert!(file_contains!(public, "sitemap.xml", "https://example.com/fr/sitemap.xml"));
+    assert!(file_contains!(public, "sitemap.xml", "https://example.com/it/sitemap.xml"));
+    assert!(file_contains!(public, "sitemap_en.xml", "https://example.com/blog/something-else/"));
+    assert!(!file_contains!(public, "sitemap_en.xml", "https://example.com/fr/blog/something/"));
+    assert!(!file_exists!(public, "en/sitemap.xml"));
+    assert!(file_contains!(public, "fr/sitemap.xml", "https://example.com/fr/blog/something/"));
+    assert!(!file_contains!(public, "fr/sitemap.xml", "https://example.com/blog/something-else/"));
+    assert!(file_exists!(public, "it/sitemap.xml"));
+}
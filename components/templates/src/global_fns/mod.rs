@@ -1,6 +1,7 @@
 extern crate error_chain;
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 use tera::{from_value, to_value, GlobalFn, Result, Value};
@@ -53,6 +54,40 @@ pub fn make_get_page(library: &Library) -> GlobalFn {
     })
 }
 
+pub fn make_get_series(library: &Library, config: &Config) -> GlobalFn {
+    let default_lang = config.default_language.clone();
+    let mut series = HashMap::new();
+    for page in library.pages_values() {
+        let name = match page.meta.series {
+            Some(ref n) if !page.series.is_empty() => n,
+            _ => continue,
+        };
+        series.entry((page.lang.clone(), name.clone())).or_insert_with(|| {
+            let pages: Vec<_> = page
+                .series
+                .iter()
+                .map(|k| library.get_page_by_key(*k).to_serialized_basic(library))
+                .collect();
+            to_value(pages).unwrap()
+        });
+    }
+
+    Box::new(move |args| -> Result<Value> {
+        let name = required_arg!(
+            String,
+            args.get("name"),
+            "`get_series` requires a `name` argument with a string value"
+        );
+        let lang = optional_arg!(String, args.get("lang"), "`get_series`: `lang` must be a string.")
+            .unwrap_or_else(|| default_lang.clone());
+
+        match series.get(&(lang, name.clone())) {
+            Some(p) => Ok(p.clone()),
+            None => Err(format!("Series `{}` not found.", name).into()),
+        }
+    })
+}
+
 pub fn make_get_section(library: &Library) -> GlobalFn {
     let mut sections = HashMap::new();
     let mut sections_basic = HashMap::new();
@@ -157,7 +192,11 @@ pub fn make_get_taxonomy_url(all_taxonomies: &[Taxonomy]) -> GlobalFn {
     for taxonomy in all_taxonomies {
         let mut items = HashMap::new();
         for item in &taxonomy.items {
-            items.insert(item.name.clone(), item.permalink.clone());
+            items.insert(item.path.clone(), item.permalink.clone());
+            // Pages can still use any of the names of a merged term
+            for variant in &item.variants {
+                items.insert(variant.clone(), item.permalink.clone());
+            }
         }
         taxonomies.insert(taxonomy.kind.name.clone(), items);
     }
@@ -194,6 +233,7 @@ pub fn make_get_taxonomy_url(all_taxonomies: &[Taxonomy]) -> GlobalFn {
 
 pub fn make_resize_image(imageproc: Arc<Mutex<imageproc::Processor>>) -> GlobalFn {
     static DEFAULT_OP: &'static str = "fill";
+    static DEFAULT_FMT: &str = "auto";
     const DEFAULT_Q: u8 = 75;
 
     Box::new(move |args| -> Result<Value> {
@@ -214,6 +254,9 @@ pub fn make_resize_image(imageproc: Arc<Mutex<imageproc::Processor>>) -> GlobalF
         );
         let op = optional_arg!(String, args.get("op"), "`resize_image`: `op` must be a string")
             .unwrap_or_else(|| DEFAULT_OP.to_string());
+        let format =
+            optional_arg!(String, args.get("format"), "`resize_image`: `format` must be a string")
+                .unwrap_or_else(|| DEFAULT_FMT.to_string());
         let quality =
             optional_arg!(u8, args.get("quality"), "`resize_image`: `quality` must be a number")
                 .unwrap_or(DEFAULT_Q);
@@ -226,7 +269,9 @@ pub fn make_resize_image(imageproc: Arc<Mutex<imageproc::Processor>>) -> GlobalF
             return Err(format!("`resize_image`: Cannot find path: {}", path).into());
         }
 
-        let imageop = imageproc::ImageOp::from_args(path.clone(), &op, width, height, quality)
+        let format = imageproc::Format::from_args(imageproc.source_path(&path), &format, quality)
+            .map_err(|e| format!("`resize_image`: {}", e))?;
+        let imageop = imageproc::ImageOp::from_args(path.clone(), &op, width, height, format)
             .map_err(|e| format!("`resize_image`: {}", e))?;
         let url = imageproc.insert(imageop);
 
@@ -234,15 +279,112 @@ pub fn make_resize_image(imageproc: Arc<Mutex<imageproc::Processor>>) -> GlobalF
     })
 }
 
+pub fn make_image_srcset(imageproc: Arc<Mutex<imageproc::Processor>>) -> GlobalFn {
+    static DEFAULT_FMT: &str = "auto";
+    const DEFAULT_Q: u8 = 75;
+
+    Box::new(move |args| -> Result<Value> {
+        let path = required_arg!(
+            String,
+            args.get("path"),
+            "`image_srcset` requires a `path` argument with a string value"
+        );
+        let widths = required_arg!(
+            Vec<u32>,
+            args.get("widths"),
+            "`image_srcset` requires a `widths` argument with an array of non-negative integers"
+        );
+        if widths.contains(&0) {
+            return Err("`image_srcset`: `widths` must all be greater than 0".to_string().into());
+        }
+        let format =
+            optional_arg!(String, args.get("format"), "`image_srcset`: `format` must be a string")
+                .unwrap_or_else(|| DEFAULT_FMT.to_string());
+        let quality =
+            optional_arg!(u8, args.get("quality"), "`image_srcset`: `quality` must be a number")
+                .unwrap_or(DEFAULT_Q);
+        if quality == 0 || quality > 100 {
+            return Err("`image_srcset`: `quality` must be in range 1-100".to_string().into());
+        }
+
+        let mut imageproc = imageproc.lock().unwrap();
+        if !imageproc.source_exists(&path) {
+            return Err(format!("`image_srcset`: Cannot find path: {}", path).into());
+        }
+        let (src_w, src_h) =
+            imageproc.source_dimensions(&path).map_err(|e| format!("`image_srcset`: {}", e))?;
+
+        // Never upscale: widths larger than the source are replaced by the source width
+        let mut widths: Vec<u32> = widths.into_iter().map(|w| w.min(src_w)).collect();
+        widths.sort();
+        widths.dedup();
+
+        let format = imageproc::Format::from_args(imageproc.source_path(&path), &format, quality)
+            .map_err(|e| format!("`image_srcset`: {}", e))?;
+
+        let mut images = Vec::with_capacity(widths.len());
+        for width in widths {
+            let op = imageproc::ResizeOp::FitWidth(width);
+            let (width, height) = op.output_dimensions(src_w, src_h);
+            let imageop = imageproc::ImageOp::new(path.clone(), op, format);
+            let mut image = HashMap::new();
+            image.insert("url".to_string(), to_value(imageproc.insert(imageop)).unwrap());
+            image.insert("width".to_string(), to_value(width).unwrap());
+            image.insert("height".to_string(), to_value(height).unwrap());
+            images.push(image);
+        }
+
+        to_value(images).map_err(|err| err.into())
+    })
+}
+
+pub fn make_get_image_metadata(content_path: PathBuf, static_path: PathBuf) -> GlobalFn {
+    Box::new(move |args| -> Result<Value> {
+        let path = required_arg!(
+            String,
+            args.get("path"),
+            "`get_image_metadata` requires a `path` argument with a string value"
+        );
+        let exif = optional_arg!(
+            bool,
+            args.get("exif"),
+            "`get_image_metadata`: `exif` must be a boolean (true or false)"
+        )
+        .unwrap_or(false);
+
+        // Images can be colocated in `content` or in `static`, content has priority.
+        // Site-absolute paths like `/img/a.png` are relative to those directories too
+        let relative_path = path.trim_left_matches('/');
+        let full_path = match [&content_path, &static_path]
+            .iter()
+            .map(|p| p.join(relative_path))
+            .find(|p| p.is_file())
+        {
+            Some(p) => p,
+            None => return Err(format!("`get_image_metadata`: Cannot find path: {}", path).into()),
+        };
+
+        let meta = imageproc::read_image_metadata(&full_path, exif)
+            .map_err(|e| format!("`get_image_metadata`: {}", e))?;
+        to_value(meta).map_err(|err| err.into())
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{make_get_taxonomy, make_get_taxonomy_url, make_get_url, make_trans};
+    use super::{
+        make_get_image_metadata, make_get_taxonomy, make_get_taxonomy_url, make_get_url,
+        make_image_srcset, make_trans,
+    };
 
     use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
 
     use tera::{to_value, Value};
 
     use config::{Config, Taxonomy as TaxonomyConfig};
+    use imageproc::Processor;
     use library::{Library, Taxonomy, TaxonomyItem};
 
     #[test]
@@ -289,8 +431,9 @@ mod tests {
     fn can_get_taxonomy() {
         let taxo_config = TaxonomyConfig { name: "tags".to_string(), ..TaxonomyConfig::default() };
         let library = Library::new(0, 0);
-        let tag = TaxonomyItem::new("Programming", "tags", &Config::default(), vec![], &library);
-        let tags = Taxonomy { kind: taxo_config, items: vec![tag] };
+        let tag = TaxonomyItem::new("Programming", &taxo_config, &Config::default(), vec![], &library);
+        let slugs = vec![(tag.slug.clone(), 0)].into_iter().collect();
+        let tags = Taxonomy { kind: taxo_config, items: vec![tag], slugs };
 
         let taxonomies = vec![tags.clone()];
         let static_fn = make_get_taxonomy(&taxonomies, &library);
@@ -328,8 +471,9 @@ mod tests {
     fn can_get_taxonomy_url() {
         let taxo_config = TaxonomyConfig { name: "tags".to_string(), ..TaxonomyConfig::default() };
         let library = Library::new(0, 0);
-        let tag = TaxonomyItem::new("Programming", "tags", &Config::default(), vec![], &library);
-        let tags = Taxonomy { kind: taxo_config, items: vec![tag] };
+        let tag = TaxonomyItem::new("Programming", &taxo_config, &Config::default(), vec![], &library);
+        let slugs = vec![(tag.slug.clone(), 0)].into_iter().collect();
+        let tags = Taxonomy { kind: taxo_config, items: vec![tag], slugs };
 
         let taxonomies = vec![tags.clone()];
         let static_fn = make_get_taxonomy_url(&taxonomies);
@@ -376,4 +520,73 @@ title = "A title"
         args.insert("lang".to_string(), to_value("fr").unwrap());
         assert_eq!(static_fn(args.clone()).unwrap(), "Un titre");
     }
+
+    #[test]
+    fn can_get_image_srcset() {
+        let content_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../../docs/content/documentation/content/image-processing");
+        let imageproc = Processor::new(content_path, Path::new("static"), "http://a.com");
+        let imageproc = Arc::new(Mutex::new(imageproc));
+        let static_fn = make_image_srcset(imageproc.clone());
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), to_value("gutenberg.jpg").unwrap());
+        args.insert("widths".to_string(), to_value(vec![600, 150, 100]).unwrap());
+
+        let res = static_fn(args).unwrap();
+        let images = res.as_array().unwrap();
+        // 600 is larger than the source and gets clamped to its width
+        assert_eq!(images.len(), 3);
+        assert_eq!(images[0]["width"], to_value(100).unwrap());
+        assert_eq!(images[0]["height"], to_value(126).unwrap());
+        assert_eq!(images[1]["width"], to_value(150).unwrap());
+        assert_eq!(images[1]["height"], to_value(190).unwrap());
+        assert_eq!(images[2]["width"], to_value(300).unwrap());
+        assert_eq!(images[2]["height"], to_value(380).unwrap());
+        assert!(images[0]["url"].as_str().unwrap().starts_with("http://a.com/processed_images/"));
+        assert!(images[0]["url"].as_str().unwrap().ends_with(".jpg"));
+        assert_eq!(imageproc.lock().unwrap().num_img_ops(), 3);
+    }
+
+    #[test]
+    fn errors_on_zero_width_in_image_srcset() {
+        let content_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../../docs/content/documentation/content/image-processing");
+        let imageproc = Processor::new(content_path, Path::new("static"), "http://a.com");
+        let imageproc = Arc::new(Mutex::new(imageproc));
+        let static_fn = make_image_srcset(imageproc.clone());
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), to_value("gutenberg.jpg").unwrap());
+        args.insert("widths".to_string(), to_value(vec![0, 150]).unwrap());
+
+        assert!(static_fn(args).is_err());
+        assert_eq!(imageproc.lock().unwrap().num_img_ops(), 0);
+    }
+
+    #[test]
+    fn can_get_image_metadata() {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../test_site");
+        let static_fn = make_get_image_metadata(root.join("content"), root.join("static"));
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), to_value("posts/with-assets/zola.png").unwrap());
+
+        let res = static_fn(args.clone()).unwrap();
+        assert_eq!(res["format"], to_value("png").unwrap());
+        assert!(res["width"].as_u64().unwrap() > 0);
+        assert!(res["height"].as_u64().unwrap() > 0);
+        assert!(res["exif"].is_null());
+
+        args.insert("path".to_string(), to_value("posts/with-assets/nope.png").unwrap());
+        assert!(static_fn(args).is_err());
+    }
+
+    #[test]
+    fn can_get_image_metadata_with_a_leading_slash() {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../test_site");
+        let static_fn = make_get_image_metadata(root.join("content"), root.join("static"));
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), to_value("/posts/with-assets/zola.png").unwrap());
+
+        let res = static_fn(args).unwrap();
+        assert_eq!(res["format"], to_value("png").unwrap());
+    }
 }
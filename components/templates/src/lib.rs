@@ -33,7 +33,9 @@ lazy_static! {
         tera.add_raw_templates(vec![
             ("404.html", include_str!("builtins/404.html")),
             ("rss.xml", include_str!("builtins/rss.xml")),
+            ("atom.xml", include_str!("builtins/atom.xml")),
             ("sitemap.xml", include_str!("builtins/sitemap.xml")),
+            ("sitemap_index.xml", include_str!("builtins/sitemap_index.xml")),
             ("robots.txt", include_str!("builtins/robots.txt")),
             ("anchor-link.html", include_str!("builtins/anchor-link.html")),
             ("shortcodes/youtube.html", include_str!("builtins/shortcodes/youtube.html")),
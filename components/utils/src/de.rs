@@ -1,13 +1,29 @@
+use serde::de::Error as DeError;
 use serde::{Deserialize, Deserializer};
 use tera::{Map, Value};
 use toml;
 
+/// A date is a native TOML datetime in TOML front matter but a plain string in YAML
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MaybeDatetime {
+    Datetime(toml::value::Datetime),
+    String(String),
+}
+
 /// Used as an attribute when we want to convert from TOML to a string date
+/// Strings are accepted as well as long as they are valid TOML datetimes, eg for YAML
 pub fn from_toml_datetime<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    toml::value::Datetime::deserialize(deserializer).map(|s| Some(s.to_string()))
+    match MaybeDatetime::deserialize(deserializer)? {
+        MaybeDatetime::Datetime(d) => Ok(Some(d.to_string())),
+        MaybeDatetime::String(s) => match s.parse::<toml::value::Datetime>() {
+            Ok(d) => Ok(Some(d.to_string())),
+            Err(_) => Err(D::Error::custom(format!("`{}` is not a valid date", s))),
+        },
+    }
 }
 
 /// Returns key/value for a converted date from TOML.
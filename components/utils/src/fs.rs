@@ -1,7 +1,11 @@
+use std::collections::HashMap;
 use std::fs::{copy, create_dir_all, read_dir, File};
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
 use walkdir::WalkDir;
 
 use errors::{Result, ResultExt};
@@ -119,6 +123,54 @@ pub fn get_file_time(path: &Path) -> Option<SystemTime> {
     })
 }
 
+/// Returns the modification time of that file as a RFC3339 datetime
+pub fn get_file_last_modified(path: &Path) -> Option<String> {
+    path.metadata()
+        .and_then(|meta| meta.modified())
+        .ok()
+        .map(|time| DateTime::<Local>::from(time).format("%Y-%m-%dT%H:%M:%S%:z").to_string())
+}
+
+/// Finds the last modification datetime of all the files in that directory, in one go.
+/// It is the date of the last commit touching a file if the directory is in a git repository
+/// and the filesystem modification time for files git doesn't know about or if there is no repository.
+pub fn get_last_modified_dates(dir: &Path) -> HashMap<PathBuf, String> {
+    let mut dates = HashMap::new();
+
+    // Most recent commits come first, only the first date seen for a file is kept
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(&["-c", "core.quotePath=false", "log", "--relative", "--format=%x00%cI"])
+        .args(&["--name-only", "--", "."])
+        .output();
+    if let Ok(output) = output {
+        if output.status.success() {
+            let mut commit_date = None;
+            for line in String::from_utf8_lossy(&output.stdout).lines() {
+                if line.starts_with('\0') {
+                    commit_date = Some(line[1..].to_string());
+                } else if let Some(ref date) = commit_date {
+                    if !line.is_empty() {
+                        dates.entry(dir.join(line)).or_insert_with(|| date.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() || dates.contains_key(entry.path()) {
+            continue;
+        }
+        if let Some(date) = get_file_last_modified(entry.path()) {
+            dates.insert(entry.path().to_path_buf(), date);
+        }
+    }
+
+    dates
+}
+
 /// Compares source and target files' timestamps and returns true if the source file
 /// has been created _or_ updated after the target file has
 pub fn file_stale<PS, PT>(p_source: PS, p_target: PT) -> bool
@@ -141,11 +193,12 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::fs::File;
+    use std::fs::{create_dir, File};
+    use std::process::Command;
 
     use tempfile::tempdir;
 
-    use super::find_related_assets;
+    use super::{find_related_assets, get_last_modified_dates};
 
     #[test]
     fn can_find_related_assets() {
@@ -162,4 +215,42 @@ mod tests {
         assert_eq!(assets.iter().filter(|p| p.file_name().unwrap() == "graph.jpg").count(), 1);
         assert_eq!(assets.iter().filter(|p| p.file_name().unwrap() == "fail.png").count(), 1);
     }
+
+    #[test]
+    fn can_get_last_modified_dates_without_git() {
+        let tmp_dir = tempdir().expect("create temp dir");
+        create_dir(tmp_dir.path().join("posts")).unwrap();
+        File::create(tmp_dir.path().join("posts").join("hello.md")).unwrap();
+
+        let dates = get_last_modified_dates(tmp_dir.path());
+        assert_eq!(dates.len(), 1);
+        assert!(dates.contains_key(&tmp_dir.path().join("posts").join("hello.md")));
+    }
+
+    #[test]
+    fn can_get_last_modified_dates_from_git() {
+        let tmp_dir = tempdir().expect("create temp dir");
+        let path = tmp_dir.path();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(path)
+                .args(&["-c", "user.name=Zola", "-c", "user.email=zola@example.com"])
+                .args(args)
+                .env("GIT_COMMITTER_DATE", "2018-11-17T12:00:00+01:00")
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        File::create(path.join("committed.md")).unwrap();
+        git(&["add", "committed.md"]);
+        git(&["commit", "-q", "-m", "Add a page"]);
+        File::create(path.join("new.md")).unwrap();
+
+        let dates = get_last_modified_dates(path);
+        assert_eq!(dates[&path.join("committed.md")], "2018-11-17T12:00:00+01:00");
+        // Not committed yet so it uses the filesystem time
+        assert!(dates.contains_key(&path.join("new.md")));
+    }
 }
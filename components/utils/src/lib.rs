@@ -1,7 +1,10 @@
 #[macro_use]
 extern crate errors;
 
+extern crate chrono;
 extern crate serde;
+#[macro_use]
+extern crate serde_derive;
 #[cfg(test)]
 extern crate tempfile;
 extern crate tera;
@@ -1,3 +1,4 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
 use std::collections::HashMap;
 use unicode_segmentation::UnicodeSegmentation;
 
@@ -12,6 +13,11 @@ pub fn get_reading_analytics(content: &str) -> (usize, usize) {
     (word_count, (word_count / 200))
 }
 
+/// Formats a datetime in UTC as RFC 3339, the format used by Atom feeds
+pub fn to_rfc3339(datetime: NaiveDateTime) -> String {
+    DateTime::<Utc>::from_utc(datetime, Utc).to_rfc3339()
+}
+
 /// Resolves an internal link (of the `./posts/something.md#hey` sort) to its absolute link
 pub fn resolve_internal_link(link: &str, permalinks: &HashMap<String, String>) -> Result<String> {
     // First we remove the ./ since that's zola specific
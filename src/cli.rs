@@ -36,6 +36,14 @@ pub fn build_cli() -> App<'static, 'static> {
                         .default_value("public")
                         .takes_value(true)
                         .help("Outputs the generated site in the given path"),
+                    Arg::with_name("drafts")
+                        .long("drafts")
+                        .takes_value(false)
+                        .help("Include drafts when loading the site"),
+                    Arg::with_name("future")
+                        .long("future")
+                        .takes_value(false)
+                        .help("Include pages dated in the future even if `build_future` is disabled"),
                 ]),
             SubCommand::with_name("serve")
                 .about("Serve the site. Rebuild and reload on change automatically")
@@ -65,7 +73,15 @@ pub fn build_cli() -> App<'static, 'static> {
                     Arg::with_name("watch_only")
                         .long("watch-only")
                         .takes_value(false)
-                        .help("Do not start a server, just re-build project on changes")
+                        .help("Do not start a server, just re-build project on changes"),
+                    Arg::with_name("drafts")
+                        .long("drafts")
+                        .takes_value(false)
+                        .help("Include drafts when loading the site"),
+                    Arg::with_name("future")
+                        .long("future")
+                        .takes_value(false)
+                        .help("Include pages dated in the future even if `build_future` is disabled"),
                 ]),
         ])
 }
@@ -3,16 +3,26 @@ use std::env;
 use errors::Result;
 use site::Site;
 
+use cmd::ContentOptions;
 use console;
 
-pub fn build(config_file: &str, base_url: Option<&str>, output_dir: &str) -> Result<()> {
+pub fn build(
+    config_file: &str,
+    base_url: Option<&str>,
+    output_dir: &str,
+    content: ContentOptions,
+) -> Result<()> {
     let mut site = Site::new(env::current_dir().unwrap(), config_file)?;
     site.set_output_path(output_dir);
     if let Some(b) = base_url {
         site.set_base_url(b.to_string());
     }
+    content.apply(&mut site);
     site.load()?;
     console::notify_site_size(&site);
     console::warn_about_ignored_pages(&site);
+    console::warn_about_broken_links(&site);
+    console::warn_about_path_collisions(&site);
+    console::warn_about_merged_terms(&site);
     site.build()
 }
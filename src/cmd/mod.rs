@@ -2,6 +2,27 @@ mod build;
 mod init;
 mod serve;
 
+use site::Site;
+
 pub use self::build::build;
 pub use self::init::create_new_project;
 pub use self::serve::serve;
+
+/// The content hidden by default that `build` and `serve` can be asked to include
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ContentOptions {
+    pub include_drafts: bool,
+    pub include_future: bool,
+}
+
+impl ContentOptions {
+    /// Includes that content in the site, to be called before loading it
+    pub fn apply(&self, site: &mut Site) {
+        if self.include_drafts {
+            site.include_drafts();
+        }
+        if self.include_future {
+            site.include_future_pages();
+        }
+    }
+}
@@ -40,6 +40,7 @@ use errors::{Result, ResultExt};
 use site::Site;
 use utils::fs::copy_file;
 
+use cmd::ContentOptions;
 use console;
 use rebuild;
 
@@ -118,6 +119,7 @@ fn create_new_site(
     output_dir: &str,
     base_url: &str,
     config_file: &str,
+    content: ContentOptions,
 ) -> Result<(Site, String)> {
     let mut site = Site::new(env::current_dir().unwrap(), config_file)?;
 
@@ -131,10 +133,14 @@ fn create_new_site(
 
     site.set_base_url(base_url);
     site.set_output_path(output_dir);
+    content.apply(&mut site);
     site.load()?;
     site.enable_live_reload(port);
     console::notify_site_size(&site);
     console::warn_about_ignored_pages(&site);
+    console::warn_about_broken_links(&site);
+    console::warn_about_path_collisions(&site);
+    console::warn_about_merged_terms(&site);
     site.build()?;
     Ok((site, address))
 }
@@ -167,9 +173,11 @@ pub fn serve(
     base_url: &str,
     config_file: &str,
     watch_only: bool,
+    content: ContentOptions,
 ) -> Result<()> {
     let start = Instant::now();
-    let (mut site, address) = create_new_site(interface, port, output_dir, base_url, config_file)?;
+    let (mut site, address) =
+        create_new_site(interface, port, output_dir, base_url, config_file, content)?;
     console::report_elapsed_time(start);
 
     // Setup watchers
@@ -393,6 +401,7 @@ pub fn serve(
                                     output_dir,
                                     base_url,
                                     config_file,
+                                    content,
                                 )
                                 .unwrap()
                                 .0;
@@ -434,6 +443,7 @@ pub fn serve(
                                     output_dir,
                                     base_url,
                                     config_file,
+                                    content,
                                 )
                                 .unwrap()
                                 .0;
@@ -69,7 +69,7 @@ pub fn warn_about_ignored_pages(site: &Site) {
 
     if !ignored_pages.is_empty() {
         warn(&format!(
-            "{} page(s) ignored (missing date or weight in a sorted section):",
+            "{} page(s) ignored (missing the field their section is sorted by):",
             ignored_pages.len()
         ));
         for path in ignored_pages {
@@ -78,6 +78,47 @@ pub fn warn_about_ignored_pages(site: &Site) {
     }
 }
 
+/// Display a warning in the console if the link checker found broken links but
+/// they should not fail the build
+pub fn warn_about_broken_links(site: &Site) {
+    if site.broken_links.is_empty() {
+        return;
+    }
+
+    for line in site.broken_links_report().lines() {
+        warn(line);
+    }
+}
+
+/// Display a warning in the console for each output path used more than once,
+/// only happens if `path_collisions_as_warnings` is set
+pub fn warn_about_path_collisions(site: &Site) {
+    if site.path_collisions.is_empty() {
+        return;
+    }
+
+    for line in site.path_collisions_report().lines() {
+        warn(line);
+    }
+}
+
+/// Display a warning in the console for each taxonomy term that was merged from
+/// several names having the same slug
+pub fn warn_about_merged_terms(site: &Site) {
+    for taxonomy in &site.taxonomies {
+        for item in taxonomy.items.iter().filter(|i| !i.variants.is_empty()) {
+            let variants: Vec<_> = item.variants.iter().map(|v| format!("`{}`", v)).collect();
+            warn(&format!(
+                "Taxonomy `{}`: {} have the same slug `{}` and were merged as `{}`",
+                taxonomy.kind.name,
+                variants.join(", "),
+                item.slug,
+                item.name
+            ));
+        }
+    }
+}
+
 /// Print the time elapsed rounded to 1 decimal
 pub fn report_elapsed_time(instant: Instant) {
     let duration_ms = Duration::from_std(instant.elapsed()).unwrap().num_milliseconds() as f64;
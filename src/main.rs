@@ -46,7 +46,11 @@ fn main() {
             console::info("Building site...");
             let start = Instant::now();
             let output_dir = matches.value_of("output_dir").unwrap();
-            match cmd::build(config_file, matches.value_of("base_url"), output_dir) {
+            let content = cmd::ContentOptions {
+                include_drafts: matches.is_present("drafts"),
+                include_future: matches.is_present("future"),
+            };
+            match cmd::build(config_file, matches.value_of("base_url"), output_dir, content) {
                 Ok(()) => console::report_elapsed_time(start),
                 Err(e) => {
                     console::unravel_errors("Failed to build the site", &e);
@@ -80,8 +84,20 @@ fn main() {
             let watch_only = matches.is_present("watch_only");
             let output_dir = matches.value_of("output_dir").unwrap();
             let base_url = matches.value_of("base_url").unwrap();
+            let content = cmd::ContentOptions {
+                include_drafts: matches.is_present("drafts"),
+                include_future: matches.is_present("future"),
+            };
             console::info("Building site...");
-            match cmd::serve(interface, port, output_dir, base_url, config_file, watch_only) {
+            match cmd::serve(
+                interface,
+                port,
+                output_dir,
+                base_url,
+                config_file,
+                watch_only,
+                content,
+            ) {
                 Ok(()) => (),
                 Err(e) => {
                     console::unravel_errors("", &e);